@@ -1,7 +1,7 @@
 use openweathermap_client::models::{City, UnitSystem};
 use openweathermap_client::{error::ClientError, Client, ClientOptions};
 
-/// Gets the temperature in °C and description of the weather in Paris right now
+/// Gets a summary of the weather in Paris right now, in French with temperatures in °C
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<(), ClientError> {
     let options = ClientOptions {
@@ -12,9 +12,6 @@ async fn main() -> Result<(), ClientError> {
     let client = Client::new(options)?;
     let reading = client.fetch_weather(&City::new("Paris", "FR")).await?;
 
-    println!(
-        "The temperature and weather in France in French is {}, {}",
-        reading.main.temp, reading.weather[0].description
-    );
+    println!("{}", client.summarizer().one_line(&reading));
     Ok(())
 }
@@ -0,0 +1,490 @@
+//! Polygons (fields), soil readings, satellite imagery and accumulated parameters from the
+//! [Agro Monitoring API](https://agromonitoring.com/api).  Requires an Agro Monitoring api key.
+
+use hyper::Method;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use url::Url;
+
+use crate::{
+    client::ApiRequest,
+    error::ApiCallError,
+    hosts::{ApiHost, Endpoint},
+    models::Coord,
+    query::QueryParameters,
+    Client,
+};
+
+/// Api docs are here <https://agromonitoring.com/api/polygons>
+const POLYGONS: Endpoint = Endpoint::new("agro_polygons", ApiHost::Agro, "/agro/1.0/polygons");
+
+/// Api docs are here <https://agromonitoring.com/api/current-soil> and <https://agromonitoring.com/api/history-soil>
+const SOIL: Endpoint = Endpoint::new("agro_soil", ApiHost::Agro, "/agro/1.0/soil");
+const SOIL_HISTORY: Endpoint = Endpoint::new("agro_soil_history", ApiHost::Agro, "/agro/1.0/soil/history");
+
+/// Api docs are here <https://agromonitoring.com/api/images>
+const IMAGE_SEARCH: Endpoint = Endpoint::new("agro_image_search", ApiHost::Agro, "/agro/1.0/image/search");
+const INDEX_STATS: Endpoint = Endpoint::new("agro_index_stats", ApiHost::Agro, "");
+
+/// Api docs are here <https://agromonitoring.com/api/accumulated-temperature-precipitation>
+const ACCUMULATED_TEMPERATURE: Endpoint = Endpoint::new(
+    "agro_accumulated_temperature",
+    ApiHost::Agro,
+    "/agro/1.0/weather/history/accumulated_temperature",
+);
+const ACCUMULATED_PRECIPITATION: Endpoint = Endpoint::new(
+    "agro_accumulated_precipitation",
+    ApiHost::Agro,
+    "/agro/1.0/weather/history/accumulated_precipitation",
+);
+
+/// A `GeoJSON` geometry.  Agro Monitoring only accepts polygons.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Geometry {
+    /// Rings of `[lon, lat]` positions, the first being the outer boundary.  Each ring ends with its first position.
+    Polygon {
+        /// The rings
+        coordinates: Vec<Vec<[f64; 2]>>,
+    },
+}
+
+/// A `GeoJSON` feature wrapping the [`Geometry`] of a polygon.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeoJson {
+    /// Always `Feature`
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// Arbitrary properties, ignored by the API
+    #[serde(default)]
+    pub properties: BTreeMap<String, serde_json::Value>,
+    /// See [`Geometry`]
+    pub geometry: Geometry,
+}
+
+impl GeoJson {
+    /// A polygon feature with the outer boundary passing through `boundary`.  The ring is closed if it isn't already.
+    pub fn polygon(boundary: &[Coord]) -> Self {
+        let mut ring: Vec<[f64; 2]> = boundary.iter().map(|c| [c.lon, c.lat]).collect();
+        if let (Some(first), Some(last)) = (ring.first().copied(), ring.last()) {
+            // Only an identical position closes a ring.
+            #[allow(clippy::float_cmp)]
+            if first != *last {
+                ring.push(first);
+            }
+        }
+        GeoJson {
+            type_: "Feature".to_string(),
+            properties: BTreeMap::new(),
+            geometry: Geometry::Polygon {
+                coordinates: vec![ring],
+            },
+        }
+    }
+}
+
+/// A polygon to create.
+#[derive(Debug, Clone, Serialize)]
+pub struct NewPolygon {
+    /// Name of the polygon
+    pub name: String,
+    /// See [`GeoJson`]
+    pub geo_json: GeoJson,
+}
+
+/// A polygon, e.g. a field, registered with the API.
+#[derive(Debug, Deserialize)]
+pub struct Polygon {
+    /// Polygon ID, used by all the per-polygon calls
+    pub id: String,
+    /// Name of the polygon
+    pub name: String,
+    /// See [`GeoJson`]
+    pub geo_json: GeoJson,
+    /// Centroid, `[lon, lat]`
+    pub center: [f64; 2],
+    /// Area, ha
+    pub area: f64,
+    /// Owner of the polygon
+    pub user_id: Option<String>,
+    /// Time of creation, unix, UTC (in seconds)
+    pub created_at: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct PolygonName<'a> {
+    name: &'a str,
+}
+
+/// Soil temperature and moisture under a polygon.
+#[derive(Debug, Deserialize)]
+pub struct Soil {
+    /// Time of data calculation, unix, UTC (in seconds)
+    pub dt: i64,
+    /// Temperature at a depth of 10cm, Kelvin
+    pub t10: f64,
+    /// Soil moisture, m³/m³
+    pub moisture: f64,
+    /// Surface temperature, Kelvin
+    pub t0: f64,
+}
+
+/// Filters for [`Client::search_satellite_images`].  Times are unix, UTC (in seconds).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SatelliteImageSearch {
+    /// Start of the period to search
+    pub start: i64,
+    /// End of the period to search
+    pub end: i64,
+    /// Only images with at most this much cloud cover, %
+    pub clouds_max: Option<u8>,
+    /// Only images covering at least this much of the polygon, %
+    pub coverage_min: Option<u8>,
+}
+
+impl SatelliteImageSearch {
+    /// Every image between `start` and `end`.
+    pub fn new(start: i64, end: i64) -> Self {
+        SatelliteImageSearch {
+            start,
+            end,
+            clouds_max: None,
+            coverage_min: None,
+        }
+    }
+
+    fn query_params(&self) -> QueryParameters {
+        let mut params = vec![("start", self.start.to_string()), ("end", self.end.to_string())];
+        if let Some(clouds_max) = self.clouds_max {
+            params.push(("clouds_max", clouds_max.to_string()));
+        }
+        if let Some(coverage_min) = self.coverage_min {
+            params.push(("coverage_min", coverage_min.to_string()));
+        }
+        params
+    }
+}
+
+/// Position of the sun when an image was taken.
+#[derive(Debug, Deserialize)]
+pub struct SunPosition {
+    /// Azimuth, degrees
+    pub azimuth: f64,
+    /// Elevation, degrees
+    pub elevation: f64,
+}
+
+/// URLs of an image, one per preset.
+#[derive(Debug, Deserialize)]
+pub struct ImagePresets {
+    /// Natural colors
+    pub truecolor: Option<String>,
+    /// False colors, highlighting vegetation
+    pub falsecolor: Option<String>,
+    /// Normalized Difference Vegetation Index
+    pub ndvi: Option<String>,
+    /// Enhanced Vegetation Index
+    pub evi: Option<String>,
+}
+
+/// URLs of the vegetation index statistics of an image.  Fetch them with [`Client::fetch_index_stats`].
+#[derive(Debug, Deserialize)]
+pub struct IndexStatsUrls {
+    /// Normalized Difference Vegetation Index
+    pub ndvi: Option<String>,
+    /// Enhanced Vegetation Index
+    pub evi: Option<String>,
+}
+
+/// A satellite image of a polygon.
+#[derive(Debug, Deserialize)]
+pub struct SatelliteImage {
+    /// Time the image was taken, unix, UTC (in seconds)
+    pub dt: i64,
+    /// Satellite, e.g. `Landsat 8` or `Sentinel-2`
+    #[serde(rename = "type")]
+    pub satellite: String,
+    /// Share of the polygon covered by the image, %
+    pub dc: f64,
+    /// Cloud cover, %
+    pub cl: f64,
+    /// See [`SunPosition`]
+    pub sun: Option<SunPosition>,
+    /// PNG images clipped to the polygon
+    pub image: ImagePresets,
+    /// Tile URL templates, with `{z}`, `{x}` and `{y}` placeholders
+    pub tile: Option<ImagePresets>,
+    /// See [`IndexStatsUrls`]
+    pub stats: IndexStatsUrls,
+    /// `GeoTIFF` images clipped to the polygon
+    pub data: Option<ImagePresets>,
+}
+
+/// Statistics of a vegetation index over the pixels of a polygon.
+#[derive(Debug, Deserialize)]
+pub struct IndexStats {
+    /// Standard deviation
+    pub std: f64,
+    /// 25th percentile
+    pub p25: f64,
+    /// Number of pixels
+    pub num: u64,
+    /// Minimum value
+    pub min: f64,
+    /// Maximum value
+    pub max: f64,
+    /// Median value
+    pub median: f64,
+    /// 75th percentile
+    pub p75: f64,
+    /// Mean value
+    pub mean: f64,
+}
+
+/// Temperature accumulated above a threshold over one day.
+#[derive(Debug, Deserialize)]
+pub struct AccumulatedTemperature {
+    /// Start of the day, unix, UTC (in seconds)
+    pub dt: i64,
+    /// Sum of the temperatures above the threshold, Kelvin
+    pub temp: f64,
+    /// Number of measurements summed
+    pub count: u64,
+}
+
+/// Precipitation accumulated over one day.
+#[derive(Debug, Deserialize)]
+pub struct AccumulatedPrecipitation {
+    /// Start of the day, unix, UTC (in seconds)
+    pub dt: i64,
+    /// Sum of the precipitation, mm
+    pub rain: f64,
+    /// Number of measurements summed
+    pub count: u64,
+}
+
+impl Client {
+    /// Register a polygon.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn create_polygon(&self, polygon: &NewPolygon) -> Result<Polygon, ApiCallError> {
+        let request = ApiRequest::new(POLYGONS, &polygon.name, Vec::new()).with_json_body(Method::POST, polygon)?;
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+
+    /// Fetch every polygon registered with the api key.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn list_polygons(&self) -> Result<Vec<Polygon>, ApiCallError> {
+        let request = ApiRequest::new(POLYGONS, "all", Vec::new());
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+
+    /// Fetch one polygon.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_polygon(&self, polygon_id: &str) -> Result<Polygon, ApiCallError> {
+        let request = polygon_request(polygon_id)?;
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+
+    /// Rename a polygon.  Its geometry can't be changed.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn rename_polygon(&self, polygon_id: &str, name: &str) -> Result<Polygon, ApiCallError> {
+        let request = polygon_request(polygon_id)?.with_json_body(Method::PUT, &PolygonName { name })?;
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+
+    /// Remove a polygon.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn delete_polygon(&self, polygon_id: &str) -> Result<(), ApiCallError> {
+        let mut request = polygon_request(polygon_id)?;
+        request.method = Method::DELETE;
+        self.call(&request).await?;
+        Ok(())
+    }
+
+    /// Fetch the current soil temperature and moisture under a polygon.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_soil(&self, polygon_id: &str) -> Result<Soil, ApiCallError> {
+        let request = ApiRequest::new(SOIL, polygon_id, vec![("polyid", polygon_id.to_string())]);
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+
+    /// Fetch the soil temperature and moisture under a polygon between `start` and `end` (unix, UTC, in seconds).
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_soil_history(&self, polygon_id: &str, start: i64, end: i64) -> Result<Vec<Soil>, ApiCallError> {
+        let params = vec![
+            ("polyid", polygon_id.to_string()),
+            ("start", start.to_string()),
+            ("end", end.to_string()),
+        ];
+        let request = ApiRequest::new(SOIL_HISTORY, polygon_id, params);
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+
+    /// Find the satellite images of a polygon.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn search_satellite_images(
+        &self,
+        polygon_id: &str,
+        search: &SatelliteImageSearch,
+    ) -> Result<Vec<SatelliteImage>, ApiCallError> {
+        let mut params = vec![("polyid", polygon_id.to_string())];
+        params.extend(search.query_params());
+        let request = ApiRequest::new(IMAGE_SEARCH, polygon_id, params);
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+
+    /// Fetch the statistics at one of an image's [`IndexStatsUrls`].  The call goes to the path of `stats_url` on the
+    /// Agro host, with the client's api key.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_index_stats(&self, stats_url: &str) -> Result<IndexStats, ApiCallError> {
+        let url = Url::parse(stats_url)?;
+        let mut request = ApiRequest::new(INDEX_STATS, url.path(), Vec::new());
+        request.path = url.path().to_string();
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+
+    /// Fetch the daily sums of temperatures above `threshold` (Kelvin) over a polygon between `start` and `end` (unix,
+    /// UTC, in seconds).
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_polygon_accumulated_temperature(
+        &self,
+        polygon_id: &str,
+        start: i64,
+        end: i64,
+        threshold: f64,
+    ) -> Result<Vec<AccumulatedTemperature>, ApiCallError> {
+        let params = vec![
+            ("polyid", polygon_id.to_string()),
+            ("start", start.to_string()),
+            ("end", end.to_string()),
+            ("threshold", threshold.to_string()),
+        ];
+        let request = ApiRequest::new(ACCUMULATED_TEMPERATURE, polygon_id, params);
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+
+    /// Fetch the daily sums of precipitation over a polygon between `start` and `end` (unix, UTC, in seconds).
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_polygon_accumulated_precipitation(
+        &self,
+        polygon_id: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<AccumulatedPrecipitation>, ApiCallError> {
+        let params = vec![
+            ("polyid", polygon_id.to_string()),
+            ("start", start.to_string()),
+            ("end", end.to_string()),
+        ];
+        let request = ApiRequest::new(ACCUMULATED_PRECIPITATION, polygon_id, params);
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+}
+
+fn polygon_request(polygon_id: &str) -> Result<ApiRequest, ApiCallError> {
+    ApiRequest::resource(POLYGONS, "agro_polygon", polygon_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polygon_rings_are_closed_lon_lat_positions() {
+        let geo_json = GeoJson::polygon(&[
+            Coord::new(37.6683, -121.1958),
+            Coord::new(37.6683, -121.1779),
+            Coord::new(37.6792, -121.1779),
+        ]);
+
+        assert_eq!(
+            serde_json::to_string(&geo_json).unwrap(),
+            r#"{"type":"Feature","properties":{},"geometry":{"type":"Polygon","coordinates":[[[-121.1958,37.6683],[-121.1779,37.6683],[-121.1779,37.6792],[-121.1958,37.6683]]]}}"#
+        );
+    }
+
+    #[test]
+    fn parse_polygon() {
+        let parsed: Polygon = serde_yaml::from_str(
+            r#"{"id":"5aaa8052cbbbb5000b73ff66","name":"Polygon Sample","center":[-121.1867,37.6739],"area":190.9484,
+                "user_id":"5a34f55f4ed2b00001e5b0a4","created_at":1521123410,
+                "geo_json":{"type":"Feature","properties":{},"geometry":{"type":"Polygon",
+                    "coordinates":[[[-121.1958,37.6683],[-121.1779,37.6683],[-121.1779,37.6792],[-121.1958,37.6683]]]}}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(parsed.id, "5aaa8052cbbbb5000b73ff66");
+        assert!((parsed.area - 190.9484).abs() < f64::EPSILON);
+        let Geometry::Polygon { coordinates } = &parsed.geo_json.geometry;
+        assert_eq!(coordinates[0].len(), 4);
+    }
+
+    #[test]
+    fn parse_satellite_images_and_stats() {
+        let parsed: Vec<SatelliteImage> = serde_yaml::from_str(
+            r#"[{"dt":1500940800,"type":"Landsat 8","dc":100,"cl":1.56,"sun":{"azimuth":126.42,"elevation":63.95},
+                 "image":{"truecolor":"http://api.agromonitoring.com/image/1.0/00059768a00/5ac22f004b1ae4000b5b97cf?appid=KEY",
+                          "ndvi":"http://api.agromonitoring.com/image/1.0/02059768a00/5ac22f004b1ae4000b5b97cf?appid=KEY"},
+                 "stats":{"ndvi":"http://api.agromonitoring.com/stats/1.0/02359768a00/5ac22f004b1ae4000b5b97cf?appid=KEY"}}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(parsed[0].satellite, "Landsat 8");
+        assert!(parsed[0].image.evi.is_none());
+        assert!(parsed[0].stats.ndvi.is_some());
+
+        let stats: IndexStats = serde_yaml::from_str(
+            r#"{"std":0.1,"p25":0.3,"num":57,"min":0.1,"max":0.8,"median":0.4,"p75":0.5,"mean":0.42}"#,
+        )
+        .unwrap();
+        assert_eq!(stats.num, 57);
+    }
+
+    #[test]
+    fn satellite_image_search_params() {
+        let search = SatelliteImageSearch {
+            clouds_max: Some(20),
+            ..SatelliteImageSearch::new(1_500_000_000, 1_510_000_000)
+        };
+        assert_eq!(
+            search.query_params(),
+            vec![
+                ("start", "1500000000".to_string()),
+                ("end", "1510000000".to_string()),
+                ("clouds_max", "20".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,475 @@
+use log::{debug, warn};
+use serde::Deserialize;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{
+    error::{ApiCallError, InvalidOptionsError},
+    hosts::ApiHost,
+};
+
+/// How long a pooled key is kept out of rotation after the API answers `429 Too Many Requests`.
+const RATE_LIMITED_KEY_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Where the client obtains its API key(s) from.
+///
+/// In yaml this is written as a single-entry map, e.g.
+///
+/// ```yaml
+/// api_key_source:
+///   file: /run/secrets/owm_api_key
+/// ```
+#[derive(Deserialize, Clone, PartialEq, Eq)]
+#[serde(try_from = "ApiKeySourceEntry")]
+pub enum ApiKeySource {
+    /// The key itself.
+    Inline(String),
+    /// The name of an environment variable holding the key.  A client reads it once, when it is created.
+    /// [`ClientOptions::masked_api_key`](crate::ClientOptions::masked_api_key) and
+    /// [`ClientOptions::mask_api_key_if_present`](crate::ClientOptions::mask_api_key_if_present) have no client, they
+    /// read it again on each call.
+    Env(String),
+    /// A file whose contents (surrounding whitespace is trimmed) are the key.  A client re-reads the file whenever
+    /// its modification time changes, so keys mounted as Docker or Kubernetes secrets can be rotated without a
+    /// restart.  Like for [`Env`](ApiKeySource::Env), the [`ClientOptions`](crate::ClientOptions) masking methods read
+    /// it again on each call.
+    File(PathBuf),
+    /// Several keys that are used round-robin.  A key that gets a `401 Unauthorized` response is taken out of
+    /// rotation for good; a key that gets a `429 Too Many Requests` response is rested for 60 seconds.  Only the
+    /// weather hosts' responses count: Agro Monitoring keys are issued separately, so its answers say nothing
+    /// about a pooled key.
+    Pool(Vec<String>),
+}
+
+/// The single-entry map form of [`ApiKeySource`].  `serde_yaml` only reads externally tagged enums from yaml tags
+/// (`!file /run/secrets/owm_api_key`), which can't be expressed in the json config files we also accept.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ApiKeySourceEntry {
+    inline: Option<String>,
+    env: Option<String>,
+    file: Option<PathBuf>,
+    pool: Option<Vec<String>>,
+}
+
+impl TryFrom<ApiKeySourceEntry> for ApiKeySource {
+    type Error = String;
+
+    fn try_from(entry: ApiKeySourceEntry) -> Result<Self, Self::Error> {
+        let mut sources = [
+            entry.inline.map(ApiKeySource::Inline),
+            entry.env.map(ApiKeySource::Env),
+            entry.file.map(ApiKeySource::File),
+            entry.pool.map(ApiKeySource::Pool),
+        ]
+        .into_iter()
+        .flatten();
+
+        match (sources.next(), sources.next()) {
+            (Some(source), None) => Ok(source),
+            _ => Err("api_key_source must have exactly one of inline, env, file or pool".to_string()),
+        }
+    }
+}
+
+impl ApiKeySource {
+    /// Ensures the source can possibly produce a key.
+    ///
+    /// # Errors
+    /// If the source is blank, or is a pool that is empty or contains a blank key.
+    pub fn validate(&self) -> Result<(), InvalidOptionsError> {
+        let problem = match self {
+            ApiKeySource::Inline(key) if key.is_empty() => Some("api_key_source.inline must be non-blank"),
+            ApiKeySource::Env(name) if name.is_empty() => Some("api_key_source.env must name an environment variable"),
+            ApiKeySource::File(path) if path.as_os_str().is_empty() => Some("api_key_source.file must be a path"),
+            ApiKeySource::Pool(keys) if keys.is_empty() => Some("api_key_source.pool must contain at least one key"),
+            ApiKeySource::Pool(keys) if keys.iter().any(String::is_empty) => {
+                Some("api_key_source.pool must not contain blank keys")
+            }
+            _ => None,
+        };
+        match problem {
+            Some(message) => Err(InvalidOptionsError {
+                message: message.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// The keys this source currently resolves to.  Environment variables and files are read on each call and
+    /// silently contribute nothing if they can't be read.
+    pub(crate) fn current_keys(&self) -> Vec<String> {
+        match self {
+            ApiKeySource::Inline(key) => vec![key.clone()],
+            ApiKeySource::Env(name) => std::env::var(name).into_iter().collect(),
+            ApiKeySource::File(path) => read_key_file(path).into_iter().collect(),
+            ApiKeySource::Pool(keys) => keys.clone(),
+        }
+        .into_iter()
+        .filter(|key| !key.is_empty())
+        .collect()
+    }
+}
+
+impl fmt::Debug for ApiKeySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiKeySource::Inline(key) => write!(f, "Inline(\"{}\")", mask(key)),
+            ApiKeySource::Env(name) => write!(f, "Env({name:?})"),
+            ApiKeySource::File(path) => write!(f, "File({path:?})"),
+            ApiKeySource::Pool(keys) => {
+                let masked: Vec<String> = keys.iter().map(|key| mask(key)).collect();
+                write!(f, "Pool({masked:?})")
+            }
+        }
+    }
+}
+
+/// Masks all but the first 3 characters of a key.
+pub(crate) fn mask(s: &str) -> String {
+    let mut masked: String = s.to_string();
+    if !s.is_empty() {
+        let range = (masked.len() - 1).clamp(0, 3)..;
+        masked.replace_range(range, "****");
+    }
+    masked
+}
+
+/// Replaces every occurrence of each of `keys` within `any_string` with its masked form.
+pub(crate) fn mask_all(keys: &[String], any_string: &str) -> String {
+    keys.iter()
+        .filter(|key| !key.is_empty())
+        .fold(any_string.to_string(), |s, key| s.replace(key.as_str(), &mask(key)))
+}
+
+fn read_key_file(path: &Path) -> Result<String, std::io::Error> {
+    std::fs::read_to_string(path).map(|contents| contents.trim().to_string())
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyState {
+    Available,
+    RestingUntil(Instant),
+    Revoked,
+}
+
+struct PooledKey {
+    key: String,
+    state: KeyState,
+}
+
+enum KeyRingState {
+    Single(String),
+    File {
+        path: PathBuf,
+        key: String,
+        modified: Option<SystemTime>,
+    },
+    Pool {
+        keys: Vec<PooledKey>,
+        next: usize,
+    },
+}
+
+/// The client's runtime view of an [`ApiKeySource`]: hands out a key for each call, watches key files for changes
+/// and keeps track of which pooled keys are out of rotation.
+pub(crate) struct KeyRing {
+    state: Mutex<KeyRingState>,
+    /// Every key ever handed out, so that all of them are masked in logs and errors.
+    seen: Mutex<Vec<String>>,
+}
+
+impl KeyRing {
+    /// Resolves the source's key(s).
+    ///
+    /// # Errors
+    /// If an environment variable is not set or a key file cannot be read.
+    pub(crate) fn new(source: &ApiKeySource) -> Result<KeyRing, InvalidOptionsError> {
+        source.validate()?;
+
+        let state = match source {
+            ApiKeySource::Inline(key) => KeyRingState::Single(key.clone()),
+            ApiKeySource::Env(name) => match std::env::var(name) {
+                Ok(key) if !key.is_empty() => KeyRingState::Single(key),
+                _ => {
+                    return Err(InvalidOptionsError {
+                        message: format!("environment variable {name} must be set to a non-blank api key"),
+                    })
+                }
+            },
+            ApiKeySource::File(path) => match read_key_file(path) {
+                Ok(key) if !key.is_empty() => KeyRingState::File {
+                    path: path.clone(),
+                    key,
+                    modified: modified(path),
+                },
+                Ok(_) => {
+                    return Err(InvalidOptionsError {
+                        message: format!("api key file {} is empty", path.to_string_lossy()),
+                    })
+                }
+                Err(e) => {
+                    return Err(InvalidOptionsError {
+                        message: format!("unable to read api key file {}. {e}", path.to_string_lossy()),
+                    })
+                }
+            },
+            ApiKeySource::Pool(keys) => KeyRingState::Pool {
+                keys: keys
+                    .iter()
+                    .map(|key| PooledKey {
+                        key: key.clone(),
+                        state: KeyState::Available,
+                    })
+                    .collect(),
+                next: 0,
+            },
+        };
+
+        Ok(KeyRing {
+            seen: Mutex::new(source.current_keys()),
+            state: Mutex::new(state),
+        })
+    }
+
+    /// The key to use for the next call.
+    ///
+    /// # Errors
+    /// If every key of a pool is out of rotation.
+    pub(crate) fn next_key(&self) -> Result<String, ApiCallError> {
+        let key = {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            match &mut *state {
+                KeyRingState::Single(key) => key.clone(),
+                KeyRingState::File { path, key, modified } => {
+                    reload_if_changed(path, key, modified);
+                    key.clone()
+                }
+                KeyRingState::Pool { keys, next } => next_pooled_key(keys, next)?,
+            }
+        };
+        self.remember(&key);
+        Ok(key)
+    }
+
+    /// Feeds the response status of a call made with `key` to `host` back into the rotation.  Agro Monitoring
+    /// statuses are ignored, see [`ApiKeySource::Pool`].
+    pub(crate) fn report_status(&self, key: &str, host: ApiHost, status: http::StatusCode) {
+        if host == ApiHost::Agro {
+            return;
+        }
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if let KeyRingState::Pool { keys, .. } = &mut *state {
+            if let Some(pooled) = keys.iter_mut().find(|p| p.key == key) {
+                match status {
+                    http::StatusCode::UNAUTHORIZED => {
+                        warn!("Removing api key {} from rotation after a 401 response", mask(key));
+                        pooled.state = KeyState::Revoked;
+                    }
+                    http::StatusCode::TOO_MANY_REQUESTS => {
+                        warn!(
+                            "Resting api key {} for {RATE_LIMITED_KEY_COOLDOWN:?} after a 429 response",
+                            mask(key)
+                        );
+                        pooled.state = KeyState::RestingUntil(Instant::now() + RATE_LIMITED_KEY_COOLDOWN);
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    /// Masks every key this ring has handed out.
+    pub(crate) fn mask_keys_if_present(&self, any_string: &str) -> String {
+        mask_all(&self.seen.lock().unwrap_or_else(PoisonError::into_inner), any_string)
+    }
+
+    fn remember(&self, key: &str) {
+        let mut seen = self.seen.lock().unwrap_or_else(PoisonError::into_inner);
+        if !seen.iter().any(|k| k == key) {
+            seen.push(key.to_string());
+        }
+    }
+}
+
+fn reload_if_changed(path: &Path, key: &mut String, last_modified: &mut Option<SystemTime>) {
+    let current = modified(path);
+    if current == *last_modified {
+        return;
+    }
+    match read_key_file(path) {
+        Ok(new_key) if !new_key.is_empty() => {
+            debug!("Reloaded api key from {}", path.to_string_lossy());
+            *key = new_key;
+            *last_modified = current;
+        }
+        Ok(_) => warn!(
+            "Api key file {} is empty, continuing with the previous key",
+            path.to_string_lossy()
+        ),
+        Err(e) => warn!(
+            "Unable to re-read api key file {}, continuing with the previous key. {e}",
+            path.to_string_lossy()
+        ),
+    }
+}
+
+fn next_pooled_key(keys: &mut [PooledKey], next: &mut usize) -> Result<String, ApiCallError> {
+    let now = Instant::now();
+    let len = keys.len();
+    for offset in 0..len {
+        let index = (*next + offset) % len;
+        let pooled = &mut keys[index];
+        if let KeyState::RestingUntil(until) = pooled.state {
+            if until <= now {
+                pooled.state = KeyState::Available;
+            }
+        }
+        if pooled.state == KeyState::Available {
+            *next = (index + 1) % len;
+            return Ok(pooled.key.clone());
+        }
+    }
+    Err(ApiCallError::NoApiKeyAvailable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::StatusCode;
+
+    fn pool(keys: &[&str]) -> KeyRing {
+        KeyRing::new(&ApiKeySource::Pool(keys.iter().map(ToString::to_string).collect())).unwrap()
+    }
+
+    #[test]
+    fn serde_parse() {
+        let parsed: Vec<ApiKeySource> = serde_yaml::from_str(
+            "\
+- inline: abc123
+- env: OWM_API_KEY
+- file: /run/secrets/owm
+- pool: [ abc, def ]
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            parsed,
+            vec![
+                ApiKeySource::Inline("abc123".to_string()),
+                ApiKeySource::Env("OWM_API_KEY".to_string()),
+                ApiKeySource::File(PathBuf::from("/run/secrets/owm")),
+                ApiKeySource::Pool(vec!["abc".to_string(), "def".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn serde_parse_requires_exactly_one_source() {
+        assert!(serde_yaml::from_str::<ApiKeySource>("{}").is_err());
+        assert!(serde_yaml::from_str::<ApiKeySource>("{ inline: abc, env: ABC }").is_err());
+        assert!(serde_yaml::from_str::<ApiKeySource>("{ vault: abc }").is_err());
+    }
+
+    #[test]
+    fn debug_masks_keys() {
+        let debug = format!(
+            "{:?} {:?}",
+            ApiKeySource::Inline("PLAINTEXT_ONE".to_string()),
+            ApiKeySource::Pool(vec!["PLAINTEXT_TWO".to_string(), "SECRET".to_string()])
+        );
+        assert!(!debug.contains("PLAINTEXT"));
+        assert!(!debug.contains("SECRET"));
+        assert!(debug.contains("PLA****"));
+        assert!(debug.contains("SEC****"));
+    }
+
+    #[test]
+    fn validate_rejects_blank_sources() {
+        assert!(ApiKeySource::Inline(String::new()).validate().is_err());
+        assert!(ApiKeySource::Env(String::new()).validate().is_err());
+        assert!(ApiKeySource::File(PathBuf::new()).validate().is_err());
+        assert!(ApiKeySource::Pool(vec![]).validate().is_err());
+        assert!(ApiKeySource::Pool(vec!["abc".to_string(), String::new()])
+            .validate()
+            .is_err());
+        assert!(ApiKeySource::Pool(vec!["abc".to_string()]).validate().is_ok());
+    }
+
+    #[test]
+    fn env_source_reads_named_variable() {
+        std::env::set_var("OWM_TEST_API_KEY_SOURCE", "from env");
+        let ring = KeyRing::new(&ApiKeySource::Env("OWM_TEST_API_KEY_SOURCE".to_string())).unwrap();
+        assert_eq!(ring.next_key().unwrap(), "from env");
+
+        assert!(KeyRing::new(&ApiKeySource::Env("OWM_TEST_API_KEY_SOURCE_UNSET".to_string())).is_err());
+    }
+
+    #[test]
+    fn file_source_is_reloaded_when_it_changes() {
+        let path = std::env::temp_dir().join(format!("owm_api_key_{}", std::process::id()));
+        std::fs::write(&path, "first_key\n").unwrap();
+
+        let ring = KeyRing::new(&ApiKeySource::File(path.clone())).unwrap();
+        assert_eq!(ring.next_key().unwrap(), "first_key");
+
+        std::fs::write(&path, "second_key").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+        assert_eq!(ring.next_key().unwrap(), "second_key");
+
+        assert_eq!(ring.mask_keys_if_present("first_key second_key"), "fir**** sec****");
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(ring.next_key().unwrap(), "second_key");
+    }
+
+    #[test]
+    fn pool_rotates_round_robin() {
+        let ring = pool(&["one", "two", "three"]);
+        let keys: Vec<String> = (0..4).map(|_| ring.next_key().unwrap()).collect();
+        assert_eq!(keys, vec!["one", "two", "three", "one"]);
+    }
+
+    #[test]
+    fn pool_skips_keys_after_401_and_429() {
+        let ring = pool(&["one", "two", "three"]);
+        ring.report_status("one", ApiHost::Api, StatusCode::UNAUTHORIZED);
+        ring.report_status("two", ApiHost::Api, StatusCode::TOO_MANY_REQUESTS);
+        ring.report_status("three", ApiHost::Api, StatusCode::OK);
+
+        assert_eq!(ring.next_key().unwrap(), "three");
+        assert_eq!(ring.next_key().unwrap(), "three");
+
+        ring.report_status("three", ApiHost::Api, StatusCode::UNAUTHORIZED);
+        assert!(matches!(ring.next_key(), Err(ApiCallError::NoApiKeyAvailable)));
+    }
+
+    #[test]
+    fn pool_ignores_agro_statuses() {
+        let ring = pool(&["one", "two"]);
+        ring.report_status("one", ApiHost::Agro, StatusCode::UNAUTHORIZED);
+        ring.report_status("two", ApiHost::Agro, StatusCode::TOO_MANY_REQUESTS);
+
+        let keys: Vec<String> = (0..2).map(|_| ring.next_key().unwrap()).collect();
+        assert_eq!(keys, vec!["one", "two"]);
+    }
+
+    #[test]
+    fn pool_masks_every_key() {
+        let ring = pool(&["first_key", "second_key"]);
+        assert_eq!(
+            ring.mask_keys_if_present("appid=first_key appid=second_key"),
+            "appid=fir**** appid=sec****"
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::client::RawResponse;
+
+/// How the client caches successful `GET` responses, so that repeated calls for the same data within `ttl` don't
+/// reach the API.  A successful `POST`, `PUT` or `DELETE` evicts the cached responses of the collection it changes,
+/// e.g. creating a polygon evicts the cached polygon list.  See [`ClientBuilder::cache`](crate::ClientBuilder::cache).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// How long a response is served from the cache.
+    pub ttl: Duration,
+    /// Responses kept at most; the oldest is evicted to make room.
+    pub max_entries: usize,
+}
+
+impl Default for CachePolicy {
+    /// 10 minutes, about how often the current weather is updated, and 1000 entries.
+    fn default() -> Self {
+        CachePolicy {
+            ttl: Duration::from_secs(600),
+            max_entries: 1000,
+        }
+    }
+}
+
+impl CachePolicy {
+    pub(crate) fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.ttl.is_zero() {
+            problems.push("cache.ttl must be greater than 0".to_string());
+        }
+        if self.max_entries == 0 {
+            problems.push("cache.max_entries must be at least 1".to_string());
+        }
+        problems
+    }
+}
+
+/// The responses cached by a client, keyed by request URL without the API key.
+pub(crate) struct ResponseCache {
+    policy: CachePolicy,
+    entries: Mutex<HashMap<String, (Instant, RawResponse)>>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(policy: CachePolicy) -> Self {
+        ResponseCache {
+            policy,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The response cached for `key`, unless it has expired.
+    pub(crate) fn get(&self, key: &str) -> Option<RawResponse> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries
+            .get(key)
+            .filter(|(cached_at, _)| cached_at.elapsed() < self.policy.ttl)
+            .map(|(_, response)| response.clone())
+    }
+
+    /// Forgets the responses of `url` and of the URLs under it, with or without query parameters.
+    pub(crate) fn evict_under(&self, url: &str) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.retain(|key, _| {
+            !key.strip_prefix(url)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?']))
+        });
+    }
+
+    pub(crate) fn insert(&self, key: String, response: RawResponse) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        if entries.len() >= self.policy.max_entries && !entries.contains_key(&key) {
+            entries.retain(|_, (cached_at, _)| cached_at.elapsed() < self.policy.ttl);
+            if entries.len() >= self.policy.max_entries {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (cached_at, _))| *cached_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, (Instant::now(), response));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::{body::Bytes, Response};
+
+    fn response(body: &'static str) -> RawResponse {
+        RawResponse {
+            url: String::new(),
+            attempt: 1,
+            elapsed: Duration::ZERO,
+            response: Response::new(Bytes::from(body)),
+        }
+    }
+
+    #[test]
+    fn responses_expire_after_the_ttl() {
+        let cache = ResponseCache::new(CachePolicy {
+            ttl: Duration::from_millis(20),
+            max_entries: 10,
+        });
+        cache.insert("a".to_string(), response("A"));
+
+        assert_eq!(cache.get("a").unwrap().body().as_ref(), b"A");
+        assert!(cache.get("b").is_none());
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(cache.get("a").is_none());
+    }
+
+    #[test]
+    fn evicting_a_url_evicts_those_under_it() {
+        let cache = ResponseCache::new(CachePolicy::default());
+        for key in [
+            "https://h/polygons?appid=K",
+            "https://h/polygons/1?appid=K",
+            "https://h/polygons_archive?appid=K",
+            "https://h/soil?polyid=1&appid=K",
+        ] {
+            cache.insert(key.to_string(), response("R"));
+        }
+
+        cache.evict_under("https://h/polygons");
+
+        assert!(cache.get("https://h/polygons?appid=K").is_none());
+        assert!(cache.get("https://h/polygons/1?appid=K").is_none());
+        assert!(cache.get("https://h/polygons_archive?appid=K").is_some());
+        assert!(cache.get("https://h/soil?polyid=1&appid=K").is_some());
+    }
+
+    #[test]
+    fn the_oldest_response_is_evicted_when_full() {
+        let cache = ResponseCache::new(CachePolicy {
+            ttl: Duration::from_secs(60),
+            max_entries: 2,
+        });
+        cache.insert("a".to_string(), response("A"));
+        std::thread::sleep(Duration::from_millis(2));
+        cache.insert("b".to_string(), response("B"));
+        cache.insert("c".to_string(), response("C"));
+
+        assert!(cache.get("a").is_none());
+        assert!(cache.get("b").is_some());
+        assert!(cache.get("c").is_some());
+    }
+}
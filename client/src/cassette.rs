@@ -0,0 +1,409 @@
+//! Record/replay of API calls.  In record mode every request and its response are written to a cassette file with the
+//! api key scrubbed out, once the client is dropped or [flushed](crate::Client::flush_cassette); in replay mode the
+//! responses are served from the cassette without touching the network, and a request that wasn't recorded is an
+//! error.
+
+use hyper::{body::Bytes, header::CONTENT_TYPE, Response, StatusCode};
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+};
+
+use crate::error::{ApiCallError, CassetteError};
+
+/// Stands in for the api key in recorded URLs and bodies.
+pub(crate) const SCRUBBED_API_KEY: &str = "SCRUBBED";
+
+/// Whether a [`Cassette`] is being written or read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CassetteMode {
+    /// Call the API and write every request/response pair to the cassette, replacing any previous contents.  The file
+    /// is written when the last clone of the client is dropped, or sooner with
+    /// [`Client::flush_cassette`](crate::Client::flush_cassette).
+    Record,
+    /// Serve responses from the cassette, never calling the API.  The api key may be left blank.
+    Replay,
+}
+
+/// A file of recorded API calls.
+///
+/// ```yaml
+/// cassette:
+///   mode: replay
+///   path: tests/cassettes/paris.yaml
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cassette {
+    /// See [`CassetteMode`]
+    pub mode: CassetteMode,
+    /// Location of the cassette file (yaml)
+    pub path: PathBuf,
+}
+
+impl Cassette {
+    /// Record to the cassette at `path`.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Cassette {
+            mode: CassetteMode::Record,
+            path: path.into(),
+        }
+    }
+
+    /// Replay from the cassette at `path`.
+    pub fn replay(path: impl Into<PathBuf>) -> Self {
+        Cassette {
+            mode: CassetteMode::Replay,
+            path: path.into(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    /// The full URL, with the api key replaced by [`SCRUBBED_API_KEY`].
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    /// The body, when it is utf-8.
+    #[serde(default)]
+    body: String,
+    /// The body, when it isn't utf-8 (e.g. map tiles).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_bytes: Option<Vec<u8>>,
+}
+
+/// A [`Cassette`] opened by the client.
+pub(crate) struct Tape {
+    cassette: Cassette,
+    state: Mutex<TapeState>,
+}
+
+struct TapeState {
+    interactions: Vec<Interaction>,
+    played: Vec<bool>,
+    /// Whether interactions were recorded since the file was last written.
+    unsaved: bool,
+}
+
+impl Tape {
+    /// Loads the cassette in replay mode, or starts an empty one in record mode.
+    ///
+    /// # Errors
+    /// If a cassette to replay can't be read or parsed.
+    pub(crate) fn open(cassette: &Cassette) -> Result<Tape, CassetteError> {
+        let file = match cassette.mode {
+            CassetteMode::Record => CassetteFile::default(),
+            CassetteMode::Replay => {
+                let contents = std::fs::read_to_string(&cassette.path).map_err(|e| error(cassette, &e))?;
+                serde_yaml::from_str(&contents).map_err(|e| error(cassette, &e))?
+            }
+        };
+        Ok(Tape {
+            cassette: cassette.clone(),
+            state: Mutex::new(TapeState {
+                played: vec![false; file.interactions.len()],
+                interactions: file.interactions,
+                unsaved: false,
+            }),
+        })
+    }
+
+    pub(crate) fn is_replaying(&self) -> bool {
+        self.cassette.mode == CassetteMode::Replay
+    }
+
+    /// The recorded response to the request, whose body has `api_key` scrubbed like when it was recorded.  Equal
+    /// requests get their recorded responses in order; once those are used up the last one is served again.
+    ///
+    /// # Errors
+    /// [`ApiCallError::CassetteMiss`] if the request wasn't recorded.
+    pub(crate) fn replay(
+        &self,
+        method: &str,
+        scrubbed_url: &str,
+        body: Option<&Bytes>,
+        api_key: &str,
+    ) -> Result<Response<Bytes>, ApiCallError> {
+        let request = recorded_request(method, scrubbed_url, body, api_key);
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let TapeState {
+            interactions, played, ..
+        } = &mut *state;
+
+        let matching: Vec<usize> = (0..interactions.len())
+            .filter(|&i| interactions[i].request == request)
+            .collect();
+        let Some(&index) = matching.iter().find(|&&i| !played[i]).or(matching.last()) else {
+            return Err(ApiCallError::CassetteMiss {
+                method: request.method,
+                url: request.url,
+            });
+        };
+        played[index] = true;
+        debug!(
+            "Replaying {} {} from {}",
+            request.method,
+            request.url,
+            self.cassette.path.display()
+        );
+
+        let recorded = &interactions[index].response;
+        let status = StatusCode::from_u16(recorded.status)
+            .map_err(|e| ApiCallError::CassetteError(error(&self.cassette, &e)))?;
+        let mut response = Response::builder().status(status);
+        if let Some(content_type) = &recorded.content_type {
+            response = response.header(CONTENT_TYPE, content_type);
+        }
+        let body = match &recorded.body_bytes {
+            Some(bytes) => Bytes::from(bytes.clone()),
+            None => Bytes::from(recorded.body.clone()),
+        };
+        Ok(response.body(body)?)
+    }
+
+    /// Appends the request and its response to the cassette, replacing `api_key` wherever it appears.  The file is
+    /// only written by [`flush`](Tape::flush).
+    pub(crate) fn record(
+        &self,
+        method: &str,
+        scrubbed_url: &str,
+        body: Option<&Bytes>,
+        response: &Response<Bytes>,
+        api_key: &str,
+    ) {
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let (body_text, body_bytes) = match std::str::from_utf8(response.body()) {
+            Ok(text) => (scrub(text, api_key), None),
+            Err(_) => (String::new(), Some(response.body().to_vec())),
+        };
+        let interaction = Interaction {
+            request: recorded_request(method, scrubbed_url, body, api_key),
+            response: RecordedResponse {
+                status: response.status().as_u16(),
+                content_type,
+                body: body_text,
+                body_bytes,
+            },
+        };
+
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.interactions.push(interaction);
+        state.played.push(true);
+        state.unsaved = true;
+    }
+
+    /// Writes the recorded interactions to the cassette file, if any were recorded since it was last written.
+    ///
+    /// # Errors
+    /// If the cassette file can't be written.
+    pub(crate) fn flush(&self) -> Result<(), CassetteError> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if !state.unsaved {
+            return Ok(());
+        }
+        let file = CassetteFile {
+            interactions: state.interactions.clone(),
+        };
+        let contents = serde_yaml::to_string(&file).map_err(|e| error(&self.cassette, &e))?;
+        std::fs::write(&self.cassette.path, contents).map_err(|e| error(&self.cassette, &e))?;
+        state.unsaved = false;
+        Ok(())
+    }
+}
+
+impl Drop for Tape {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            error!("Unable to write the recorded API calls. {e}");
+        }
+    }
+}
+
+fn recorded_request(method: &str, scrubbed_url: &str, body: Option<&Bytes>, api_key: &str) -> RecordedRequest {
+    RecordedRequest {
+        method: method.to_string(),
+        url: scrubbed_url.to_string(),
+        body: body.map(|b| scrub(&String::from_utf8_lossy(b), api_key)),
+    }
+}
+
+fn scrub(text: &str, api_key: &str) -> String {
+    if api_key.is_empty() {
+        text.to_string()
+    } else {
+        text.replace(api_key, SCRUBBED_API_KEY)
+    }
+}
+
+fn error(cassette: &Cassette, e: &impl std::fmt::Display) -> CassetteError {
+    CassetteError {
+        path: cassette.path.clone(),
+        message: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cassette(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("owm_cassette_{name}_{}.yaml", std::process::id()))
+    }
+
+    #[test]
+    fn recorded_interactions_replay_with_the_key_scrubbed() {
+        let path = temp_cassette("round_trip");
+        let recorder = Tape::open(&Cassette::record(&path)).unwrap();
+        let url = "https://api.openweathermap.org/data/2.5/weather?q=Paris%2CFR&appid=SCRUBBED";
+        let response = Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Bytes::from(r#"{"echo":"appid=SECRET_KEY"}"#))
+            .unwrap();
+        recorder.record("GET", url, None, &response, "SECRET_KEY");
+        recorder.flush().unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("SECRET_KEY"));
+
+        let player = Tape::open(&Cassette::replay(&path)).unwrap();
+        let replayed = player.replay("GET", url, None, "").unwrap();
+        assert_eq!(replayed.status(), StatusCode::OK);
+        assert_eq!(replayed.headers()[CONTENT_TYPE], "application/json");
+        assert_eq!(replayed.body().as_ref(), br#"{"echo":"appid=SCRUBBED"}"#);
+
+        // Served again once used up
+        assert!(player.replay("GET", url, None, "").is_ok());
+
+        assert!(matches!(
+            player.replay(
+                "GET",
+                "https://api.openweathermap.org/data/2.5/weather?q=Rome",
+                None,
+                ""
+            ),
+            Err(ApiCallError::CassetteMiss { .. })
+        ));
+        assert!(matches!(
+            player.replay("POST", url, None, ""),
+            Err(ApiCallError::CassetteMiss { .. })
+        ));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn equal_requests_replay_in_recorded_order() {
+        let path = temp_cassette("order");
+        let recorder = Tape::open(&Cassette::record(&path)).unwrap();
+        for status in [StatusCode::TOO_MANY_REQUESTS, StatusCode::OK] {
+            let response = Response::builder().status(status).body(Bytes::new()).unwrap();
+            recorder.record("GET", "https://x/y", None, &response, "KEY");
+        }
+        drop(recorder);
+
+        let player = Tape::open(&Cassette::replay(&path)).unwrap();
+        assert_eq!(
+            player.replay("GET", "https://x/y", None, "").unwrap().status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            player.replay("GET", "https://x/y", None, "").unwrap().status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            player.replay("GET", "https://x/y", None, "").unwrap().status(),
+            StatusCode::OK
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn request_bodies_are_scrubbed() {
+        let path = temp_cassette("body");
+        let recorder = Tape::open(&Cassette::record(&path)).unwrap();
+        let body = Bytes::from(r#"{"external_id":"SECRET_KEY"}"#);
+        let response = Response::builder()
+            .status(StatusCode::CREATED)
+            .body(Bytes::new())
+            .unwrap();
+        recorder.record("POST", "https://x/y", Some(&body), &response, "SECRET_KEY");
+        recorder.flush().unwrap();
+
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("SECRET_KEY"));
+        let player = Tape::open(&Cassette::replay(&path)).unwrap();
+        assert_eq!(
+            player
+                .replay("POST", "https://x/y", Some(&body), "SECRET_KEY")
+                .unwrap()
+                .status(),
+            StatusCode::CREATED
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn the_file_is_written_once_flushed_or_dropped() {
+        let path = temp_cassette("flush");
+        let recorder = Tape::open(&Cassette::record(&path)).unwrap();
+        let response = Response::builder().status(StatusCode::OK).body(Bytes::new()).unwrap();
+        recorder.record("GET", "https://x/y", None, &response, "KEY");
+        assert!(!path.exists());
+
+        recorder.flush().unwrap();
+        assert!(path.exists());
+        std::fs::remove_file(&path).unwrap();
+
+        // Nothing new to write
+        recorder.flush().unwrap();
+        assert!(!path.exists());
+
+        recorder.record("GET", "https://x/z", None, &response, "KEY");
+        drop(recorder);
+        assert_eq!(
+            Tape::open(&Cassette::replay(&path))
+                .unwrap()
+                .state
+                .lock()
+                .unwrap()
+                .interactions
+                .len(),
+            2
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn replaying_a_missing_cassette_fails() {
+        assert!(Tape::open(&Cassette::replay("/nonexistent/cassette.yaml")).is_err());
+    }
+}
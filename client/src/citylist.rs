@@ -0,0 +1,274 @@
+//! An offline index of `OpenWeatherMap`'s city list, behind the `citylist` feature.  The list is published at
+//! <https://bulk.openweathermap.org/sample/city.list.json.gz> and maps every [`CityId`] to a name, country, state and
+//! coordinates, so that ambiguous names can be resolved without spending API calls on geocoding.
+//!
+//! ```no_run
+//! # fn example() -> Result<(), openweathermap_client::error::CityListError> {
+//! use openweathermap_client::citylist::CityList;
+//!
+//! let cities = CityList::load("city.list.json.gz")?;
+//! for city in cities.find("Springfield", Some("US")) {
+//!     println!("{} {}: {:?}", city.id, city.state, city.query());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+use crate::{
+    error::CityListError,
+    geo::haversine_km,
+    models::{CityId, Coord},
+};
+
+/// Fuzzy matches scoring less than this are left out of [`CityList::search`].
+const MIN_SEARCH_SCORE: f64 = 0.6;
+
+/// One entry of the city list.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CityRecord {
+    /// The [`CityId`] of the city
+    pub id: u32,
+    /// Name of the city
+    pub name: String,
+    /// ISO 3166-2 subdivision code without the country prefix, only given for US cities
+    #[serde(default)]
+    pub state: String,
+    /// ISO 3166-1 alpha-2 country code
+    pub country: String,
+    /// Location of the city
+    pub coord: Coord,
+}
+
+impl CityRecord {
+    /// A [`CityId`] query for the city, displayed as `{name}, {state}, {country}` (without an empty state).
+    pub fn query(&self) -> CityId {
+        let display_name = [self.name.as_str(), self.state.as_str(), self.country.as_str()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+        CityId {
+            id: self.id,
+            display_name: Some(display_name),
+        }
+    }
+}
+
+/// The city list, indexed by id and name.
+pub struct CityList {
+    cities: Vec<CityRecord>,
+    by_id: HashMap<u32, usize>,
+    by_name: HashMap<String, Vec<usize>>,
+}
+
+impl CityList {
+    /// Loads the city list from `path`, gunzipping it if the name ends in `.gz`.
+    ///
+    /// # Errors
+    /// If the file can't be read or isn't a city list.
+    pub fn load(path: impl AsRef<Path>) -> Result<CityList, CityListError> {
+        let path = path.as_ref();
+        let error = |message: String| CityListError {
+            path: path.to_path_buf(),
+            message,
+        };
+        let file = BufReader::new(File::open(path).map_err(|e| error(e.to_string()))?);
+        let gzipped = path.extension().is_some_and(|extension| extension == "gz");
+        let result = if gzipped {
+            CityList::from_reader(GzDecoder::new(file))
+        } else {
+            CityList::from_reader(file)
+        };
+        result.map_err(|e| error(e.to_string()))
+    }
+
+    /// Reads an uncompressed city list (a JSON array of [`CityRecord`]s).
+    ///
+    /// # Errors
+    /// If the contents can't be read or aren't a city list.
+    pub fn from_reader(reader: impl Read) -> Result<CityList, serde_json::Error> {
+        Ok(CityList::from_records(serde_json::from_reader(reader)?))
+    }
+
+    /// Indexes `cities`.
+    pub fn from_records(cities: Vec<CityRecord>) -> CityList {
+        let mut by_id = HashMap::with_capacity(cities.len());
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, city) in cities.iter().enumerate() {
+            by_id.insert(city.id, index);
+            by_name.entry(normalize(&city.name)).or_default().push(index);
+        }
+        CityList { cities, by_id, by_name }
+    }
+
+    /// Number of cities in the list.
+    pub fn len(&self) -> usize {
+        self.cities.len()
+    }
+
+    /// Whether the list has no cities.
+    pub fn is_empty(&self) -> bool {
+        self.cities.is_empty()
+    }
+
+    /// The city with `id`.
+    pub fn get(&self, id: u32) -> Option<&CityRecord> {
+        self.by_id.get(&id).map(|&index| &self.cities[index])
+    }
+
+    /// The cities named `name` (ignoring case and surrounding whitespace), optionally only those in `country`.
+    pub fn find(&self, name: &str, country: Option<&str>) -> Vec<&CityRecord> {
+        self.by_name
+            .get(&normalize(name))
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.cities[index])
+            .filter(|city| country.is_none_or(|country| city.country.eq_ignore_ascii_case(country)))
+            .collect()
+    }
+
+    /// Up to `limit` cities whose name resembles `name`, best first, with their score between 0 and 1 (an exact
+    /// match).  Tolerates typos and partial names, e.g. `"Sprinfield"` or `"San Fran"`.
+    pub fn search(&self, name: &str, limit: usize) -> Vec<(&CityRecord, f64)> {
+        let wanted = normalize(name);
+        let mut matches: Vec<(&CityRecord, f64)> = self
+            .by_name
+            .iter()
+            .map(|(candidate, indexes)| (similarity(&wanted, candidate), indexes))
+            .filter(|(score, _)| *score >= MIN_SEARCH_SCORE)
+            .flat_map(|(score, indexes)| indexes.iter().map(move |&index| (&self.cities[index], score)))
+            .collect();
+        matches.sort_by(|(a, a_score), (b, b_score)| b_score.total_cmp(a_score).then(a.id.cmp(&b.id)));
+        matches.truncate(limit);
+        matches
+    }
+
+    /// The city closest to `coord`, with its distance in km.
+    pub fn nearest(&self, coord: &Coord) -> Option<(&CityRecord, f64)> {
+        self.cities
+            .iter()
+            .map(|city| (city, haversine_km(coord, &city.coord)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// 1 for equal names, 0.95 for a prefix, otherwise 1 less the edit distance relative to the longer name.
+fn similarity(wanted: &str, candidate: &str) -> f64 {
+    if wanted == candidate {
+        return 1.0;
+    }
+    if !wanted.is_empty() && candidate.starts_with(wanted) {
+        return 0.95;
+    }
+    let longest = wanted.chars().count().max(candidate.chars().count());
+    #[allow(clippy::cast_precision_loss)] // names are far shorter than 2^52 characters
+    let score = 1.0 - levenshtein(wanted, candidate) as f64 / longest as f64;
+    score
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    const CITIES: &str = r#"[
+        {"id":4409896,"name":"Springfield","state":"MO","country":"US","coord":{"lon":-93.298241,"lat":37.215328}},
+        {"id":4250542,"name":"Springfield","state":"IL","country":"US","coord":{"lon":-89.64371,"lat":39.801159}},
+        {"id":2637891,"name":"Springfield","state":"","country":"GB","coord":{"lon":-2.2,"lat":53.5}},
+        {"id":2988507,"name":"Paris","state":"","country":"FR","coord":{"lon":2.3488,"lat":48.853409}},
+        {"id":5391959,"name":"San Francisco","state":"CA","country":"US","coord":{"lon":-122.419418,"lat":37.774929}}
+    ]"#;
+
+    fn cities() -> CityList {
+        CityList::from_reader(CITIES.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn find_by_exact_name_and_country() {
+        let cities = cities();
+
+        assert_eq!(cities.find("springfield ", None).len(), 3);
+        let ids: Vec<u32> = cities.find("Springfield", Some("us")).iter().map(|c| c.id).collect();
+        assert_eq!(ids, [4_409_896, 4_250_542]);
+        assert!(cities.find("Atlantis", None).is_empty());
+    }
+
+    #[test]
+    fn fuzzy_search_ranks_the_closest_names_first() {
+        let cities = cities();
+
+        let found = cities.search("Sprinfield", 10);
+        assert_eq!(found.len(), 3);
+        assert!(found.iter().all(|(city, _)| city.name == "Springfield"));
+
+        let found = cities.search("San Fran", 1);
+        assert_eq!(found[0].0.id, 5_391_959);
+        assert!((found[0].1 - 0.95).abs() < f64::EPSILON);
+
+        assert!(cities.search("Tokyo", 10).is_empty());
+    }
+
+    #[test]
+    fn nearest_city_and_id_lookup() {
+        let cities = cities();
+
+        let (nearest, distance) = cities.nearest(&Coord::new(39.78, -89.65)).unwrap();
+        assert_eq!(nearest.state, "IL");
+        assert!(distance < 5.0);
+
+        let paris = cities.get(2_988_507).unwrap();
+        assert_eq!(paris.name, "Paris");
+        assert!(cities.get(1).is_none());
+    }
+
+    #[test]
+    fn queries_name_the_state() {
+        let query = cities().get(4_250_542).unwrap().query();
+
+        assert_eq!(query.id, 4_250_542);
+        assert_eq!(query.to_string(), "Springfield, IL, US");
+        assert_eq!(cities().get(2_988_507).unwrap().query().to_string(), "Paris, FR");
+    }
+
+    #[test]
+    fn gzipped_lists_are_loaded() {
+        let path = std::env::temp_dir().join(format!("owm_city_list_{}.json.gz", std::process::id()));
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(CITIES.as_bytes()).unwrap();
+        std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+        let cities = CityList::load(&path).unwrap();
+        assert_eq!(cities.len(), 5);
+
+        std::fs::remove_file(path).unwrap();
+        assert!(CityList::load("/nonexistent/city.list.json.gz").is_err());
+    }
+}
@@ -1,137 +1,677 @@
-use http_body_util::{BodyExt, Empty};
 use hyper::{
-    body::{Bytes, Incoming},
-    Response, StatusCode, Uri,
-};
-use hyper_rustls::HttpsConnector;
-use hyper_util::{
-    client::legacy::{connect::HttpConnector, Client as HyperClient},
-    rt::TokioExecutor,
+    body::Bytes,
+    header::{HeaderValue, CONTENT_TYPE, RETRY_AFTER, USER_AGENT},
+    HeaderMap, Method, Request, Response, StatusCode, Uri,
 };
 use log::{debug, trace};
-use std::str::FromStr;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use url::Url;
 
 use crate::{
-    error::{ApiCallError, ClientError},
-    models::CurrentWeather,
+    api_key::{ApiKeySource, KeyRing},
+    builder::ClientBuilder,
+    cache::ResponseCache,
+    cassette::{Tape, SCRUBBED_API_KEY},
+    error::{ApiCallError, CassetteError, ClientError},
+    hosts::{ApiHost, Endpoint},
+    instrumentation::CallSpan,
+    models::{CurrentWeather, UnitSystem},
+    observer::{ClientObserver, RequestInfo},
     options::ClientOptions,
+    query::QueryParameters,
+    retry::{RetryDelay, RetryPolicy},
+    transport::{HttpSettings, HyperTransport, Transport, TransportError},
     Query,
 };
 
-pub type HttpClient = HyperClient<HttpsConnector<HttpConnector>, Empty<Bytes>>;
-
 /// Api docs are here <https://openweathermap.org/current>
-const V25_ENDPOINT: &str = "https://api.openweathermap.org/data/2.5/weather";
+const V25_WEATHER: Endpoint = Endpoint::new("weather", ApiHost::Api, "/data/2.5/weather");
 
-//
+/// Sent unless another `User-Agent` is set with [`ClientBuilder::user_agent`].
+pub(crate) const DEFAULT_USER_AGENT: &str = concat!("openweathermap_client/", env!("CARGO_PKG_VERSION"));
+
+/// Clones share the api key rotation, transport and cassette; observers added to a clone only apply to that clone.
+#[derive(Clone)]
 pub struct Client {
     options: ClientOptions,
-    http_client: HttpClient,
+    keys: Arc<KeyRing>,
+    transport: Arc<dyn Transport>,
+    observers: Vec<Arc<dyn ClientObserver>>,
+    tape: Option<Arc<Tape>>,
+    settings: Arc<CallSettings>,
+}
+
+/// How calls are made, beyond the [`ClientOptions`].  Set with a [`ClientBuilder`].
+pub(crate) struct CallSettings {
+    /// Sent with every request, including the `User-Agent`.
+    pub(crate) headers: HeaderMap,
+    pub(crate) request_timeout: Option<Duration>,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) cache: Option<ResponseCache>,
+}
+
+impl Default for CallSettings {
+    fn default() -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(USER_AGENT, HeaderValue::from_static(DEFAULT_USER_AGENT));
+        CallSettings {
+            headers,
+            request_timeout: None,
+            retry: None,
+            cache: None,
+        }
+    }
+}
+
+/// An attempt that failed, with what's needed to decide on a retry.
+struct FailedAttempt {
+    error: ApiCallError,
+    /// The request URL with the API key masked, empty if the request couldn't be formed.
+    url: String,
+    /// The wait asked for by a `429` response.
+    retry_after: Option<Duration>,
+}
+
+impl From<ApiCallError> for FailedAttempt {
+    fn from(error: ApiCallError) -> Self {
+        FailedAttempt {
+            error,
+            url: String::new(),
+            retry_after: None,
+        }
+    }
 }
 
 impl Client {
-    /// Create a new client using the supplied options.
+    /// A [`ClientBuilder`] to configure a client beyond its [`ClientOptions`], e.g. with timeouts, retries, a cache
+    /// or extra request headers.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// Create a new client using the supplied options.  Only https is used unless
+    /// [`allow_insecure_http`](ClientOptions::allow_insecure_http) is set.
     ///
     /// # Errors
     /// Returns an error if invalid [`ClientOptions`] are provided.
     pub fn new(options: ClientOptions) -> Result<Client, ClientError> {
         options.validate()?;
+        let transport = if options.allow_insecure_http {
+            HyperTransport::with_settings(&HttpSettings {
+                allow_insecure_http: true,
+                ..HttpSettings::default()
+            })?
+        } else {
+            HyperTransport::new()?
+        };
+        Client::with_transport(options, Arc::new(transport))
+    }
 
-        let https = hyper_rustls::HttpsConnectorBuilder::new()
-            .with_native_roots()?
-            .https_only()
-            .enable_http1()
-            .build();
+    /// Create a new client that sends its requests with `transport` instead of a [`HyperTransport`].  A cassette in
+    /// the options is still honoured, in front of the transport.
+    ///
+    /// # Errors
+    /// Returns an error if invalid [`ClientOptions`] are provided.
+    pub fn with_transport(options: ClientOptions, transport: Arc<dyn Transport>) -> Result<Client, ClientError> {
+        Client::with_settings(options, transport, CallSettings::default())
+    }
 
-        let c = HyperClient::builder(TokioExecutor::new()).build(https);
+    pub(crate) fn with_settings(
+        options: ClientOptions,
+        transport: Arc<dyn Transport>,
+        settings: CallSettings,
+    ) -> Result<Client, ClientError> {
+        options.validate()?;
+        let keys = match options.effective_api_key_source() {
+            // Replayed URLs hold the scrubbed key, so a replaying client can do without one
+            ApiKeySource::Inline(key) if key.is_empty() && options.is_replaying() => {
+                KeyRing::new(&ApiKeySource::Inline(SCRUBBED_API_KEY.to_string()))?
+            }
+            source => KeyRing::new(&source)?,
+        };
+        let keys = Arc::new(keys);
+        let tape = match &options.cassette {
+            Some(cassette) => Some(Arc::new(Tape::open(cassette)?)),
+            None => None,
+        };
 
         Ok(Client {
             options,
-            http_client: c,
+            keys,
+            transport,
+            observers: Vec::new(),
+            tape,
+            settings: Arc::new(settings),
         })
     }
 
+    pub(crate) fn units(&self) -> UnitSystem {
+        self.options.units
+    }
+
+    pub(crate) fn language(&self) -> &str {
+        &self.options.language
+    }
+
+    /// Writes the calls recorded so far to the cassette file.  A recording client writes the file once, when its last
+    /// clone is dropped; flushing writes it sooner, and surfaces any error doing so.  Does nothing without a cassette
+    /// or when replaying.
+    ///
+    /// # Errors
+    /// If the cassette file can't be written.
+    pub fn flush_cassette(&self) -> Result<(), CassetteError> {
+        match &self.tape {
+            Some(tape) => tape.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Registers an observer to be notified of every subsequent API call's lifecycle events.
+    pub fn add_observer(&mut self, observer: Arc<dyn ClientObserver>) {
+        self.observers.push(observer);
+    }
+
     /// Fetch the weather for the provided [`Query`].
     ///
     /// # Errors
     /// May fail for a variety of reasons, See [`ApiCallError`].
     pub async fn fetch_weather(&self, query: &dyn Query) -> Result<CurrentWeather, ApiCallError> {
-        let query_url = self.url_for(query)?;
+        self.get(V25_WEATHER, query, Vec::new()).await
+    }
+
+    /// Fetch the weather for the provided [`Query`] without parsing it, see [`RawResponse::parse`].
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_weather_raw(&self, query: &dyn Query) -> Result<RawResponse, ApiCallError> {
+        self.get_raw(V25_WEATHER, query, Vec::new()).await
+    }
+
+    /// Calls the `endpoint` with the query's parameters plus `params` and parses the response into `T`.
+    pub(crate) async fn get<T: DeserializeOwned>(
+        &self,
+        endpoint: Endpoint,
+        query: &dyn Query,
+        params: QueryParameters,
+    ) -> Result<T, ApiCallError> {
+        let request = self.query_request(endpoint, query, params);
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+
+    /// Calls the `endpoint` with the query's parameters plus `params`, leaving the response unparsed.
+    pub(crate) async fn get_raw(
+        &self,
+        endpoint: Endpoint,
+        query: &dyn Query,
+        params: QueryParameters,
+    ) -> Result<RawResponse, ApiCallError> {
+        self.call(&self.query_request(endpoint, query, params)).await
+    }
+
+    /// Describes a call to `endpoint` for `query`, with the client's units and language.
+    pub(crate) fn query_request(&self, endpoint: Endpoint, query: &dyn Query, params: QueryParameters) -> ApiRequest {
+        let mut all_params = vec![
+            ("units", self.options.units.to_string()),
+            ("lang", self.options.language.clone()),
+        ];
+        all_params.extend(query.query_params());
+        all_params.extend(params);
+
+        ApiRequest {
+            endpoint: endpoint.name,
+            method: Method::GET,
+            host: endpoint.host,
+            path: endpoint.path.to_string(),
+            subject: query.to_string(),
+            params: all_params,
+            body: None,
+        }
+    }
+
+    /// Serves the request from the cache or sends it, retrying as configured.  Only successful (2xx) responses are
+    /// returned, any other status is an error.  A successful call changing a resource, anything but a `GET`, evicts
+    /// the cached responses of its collection.
+    pub(crate) async fn call(&self, request: &ApiRequest) -> Result<RawResponse, ApiCallError> {
+        let cache = self.settings.cache.as_ref();
+        let cache_key = match cache {
+            Some(_) if request.method == Method::GET => {
+                Some(url_for(self.options.base_url(request.host), request, SCRUBBED_API_KEY)?.to_string())
+            }
+            _ => None,
+        };
+        if let (Some(cache), Some(key)) = (cache, &cache_key) {
+            let info = request.info(key, 1);
+            if let Some(response) = cache.get(key) {
+                debug!("{} {} served from the cache", request.method, request.endpoint);
+                self.notify(|o| o.on_cache_hit(&info));
+                return Ok(response);
+            }
+            self.notify(|o| o.on_cache_miss(&info));
+        }
+
+        let mut attempt = 1;
+        loop {
+            let failure = match self.attempt(request, attempt).await {
+                Ok(response) => {
+                    match (cache, cache_key) {
+                        (Some(cache), Some(key)) => cache.insert(key, response.clone()),
+                        (Some(cache), None) => {
+                            cache.evict_under(collection_url(self.options.base_url(request.host), request)?.as_str());
+                        }
+                        (None, _) => {}
+                    }
+                    return Ok(response);
+                }
+                Err(failure) => failure,
+            };
+            let delay = self
+                .settings
+                .retry
+                .as_ref()
+                .and_then(|retry| retry.delay(&request.method, attempt, &failure.error, failure.retry_after));
+            let Some(delay) = delay else {
+                return Err(failure.error);
+            };
+
+            let info = request.info(&failure.url, attempt);
+            let wait = match delay {
+                RetryDelay::RateLimited(wait) => {
+                    self.notify(|o| o.on_retry_after(&info, wait));
+                    wait
+                }
+                RetryDelay::Backoff(wait) => {
+                    self.notify(|o| o.on_retry(&info, wait));
+                    wait
+                }
+            };
+            debug!("Retrying {} in {wait:?} after: {}", request.endpoint, failure.error);
+            tokio::time::sleep(wait).await;
+            attempt += 1;
+        }
+    }
+
+    /// Sends the request once, with the next API key.
+    async fn attempt(&self, request: &ApiRequest, attempt: u32) -> Result<RawResponse, FailedAttempt> {
+        let api_key = self.keys.next_key()?;
+        let query_url = url_for(self.options.base_url(request.host), request, &api_key)?;
+        let masked_url = self.keys.mask_keys_if_present(query_url.as_str());
+
+        debug!("{} {} at URL {masked_url}", request.method, request.endpoint);
+
+        let info = request.info(&masked_url, attempt);
+        let span = CallSpan::new(info.endpoint, info.url, info.query, info.attempt);
+        span.instrument(async {
+            self.notify(|o| o.on_request_start(&info));
+            let start = Instant::now();
+            let response = match self.send(request, &query_url, &api_key, &masked_url).await {
+                Ok(response) => response,
+                Err(error) => {
+                    let latency = start.elapsed();
+                    span.record_latency(latency);
+                    self.notify(|o| o.on_error(&info, &error, latency));
+                    return Err(FailedAttempt {
+                        error,
+                        url: masked_url.clone(),
+                        retry_after: None,
+                    });
+                }
+            };
+            let status = response.status();
+            let latency = start.elapsed();
+            debug!("status: {status}");
+            span.record_status(status);
+            span.record_latency(latency);
+            self.notify(|o| o.on_response(&info, status, latency));
+            self.keys.report_status(&api_key, request.host, status);
+            if status.is_success() {
+                Ok(RawResponse {
+                    url: masked_url.clone(),
+                    attempt: info.attempt,
+                    elapsed: latency,
+                    response,
+                })
+            } else {
+                Err(FailedAttempt {
+                    error: unsuccessful_response(&response),
+                    url: masked_url.clone(),
+                    retry_after: retry_after(&response),
+                })
+            }
+        })
+        .await
+    }
+
+    /// Sends the request with the transport, or to the cassette when one is in use.
+    async fn send(
+        &self,
+        request: &ApiRequest,
+        query_url: &Url,
+        api_key: &str,
+        masked_url: &str,
+    ) -> Result<Response<Bytes>, ApiCallError> {
+        let scrubbed_url = match &self.tape {
+            Some(_) => Some(url_for(self.options.base_url(request.host), request, SCRUBBED_API_KEY)?),
+            None => None,
+        };
+        if let (Some(tape), Some(scrubbed_url)) = (&self.tape, &scrubbed_url) {
+            if tape.is_replaying() {
+                return tape.replay(
+                    request.method.as_str(),
+                    scrubbed_url.as_str(),
+                    request.body.as_ref(),
+                    api_key,
+                );
+            }
+        }
 
         let uri = match Uri::from_str(query_url.as_str()) {
             Ok(u) => Ok(u),
             Err(invalid_uri) => Err(ApiCallError::ErrorFormingUri(invalid_uri)),
         }?;
+        let mut http_request = Request::builder().method(request.method.clone()).uri(uri);
+        for (name, value) in &self.settings.headers {
+            http_request = http_request.header(name, value);
+        }
+        if request.body.is_some() {
+            http_request = http_request.header(CONTENT_TYPE, "application/json");
+        }
+        let http_request = http_request.body(request.body.clone().unwrap_or_default())?;
 
-        debug!(
-            "Fetch weather at URL {}",
-            self.options.mask_api_key_if_present(query_url.as_str())
-        );
-
-        match self.http_client.get(uri).await {
-            Ok(response_body) => {
-                debug!("status: {}", response_body.status());
-                match response_body.status() {
-                    StatusCode::OK => Ok(self.handle_200_response(response_body).await?),
-                    sc => Err(self.handle_non_200_response(response_body, &sc).await),
-                }
+        let sent = self.transport.send(http_request);
+        let sent = match self.settings.request_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, sent)
+                .await
+                .unwrap_or_else(|_| Err(TransportError::from(format!("no response within {timeout:?}")))),
+            None => sent.await,
+        };
+        let response = match sent {
+            Ok(response) => response,
+            Err(error) => {
+                return Err(ApiCallError::HttpError {
+                    error,
+                    url: masked_url.to_string(),
+                })
             }
-            Err(error) => Err(ApiCallError::HttpError {
-                error,
-                url: self.options.mask_api_key_if_present(query_url.as_str()),
-            }),
-        }
-    }
-
-    fn url_for(&self, query: &dyn Query) -> Result<Url, ApiCallError> {
-        match Url::parse(V25_ENDPOINT) {
-            Ok(mut url) => {
-                {
-                    let mut query_pairs = url.query_pairs_mut();
-                    query_pairs
-                        .append_pair("units", &self.options.units.to_string())
-                        .append_pair("lang", &self.options.language)
-                        .append_pair("appid", &self.options.api_key);
-                    for p in query.query_params() {
-                        query_pairs.append_pair(p.0, &p.1);
-                    }
-                }
-                Ok(url)
+        };
+
+        if let (Some(tape), Some(scrubbed_url)) = (&self.tape, &scrubbed_url) {
+            tape.record(
+                request.method.as_str(),
+                scrubbed_url.as_str(),
+                request.body.as_ref(),
+                &response,
+                api_key,
+            );
+        }
+        Ok(response)
+    }
+
+    /// Parses a response body into `T`, notifying the observers of a failure.
+    pub(crate) fn parse<T: DeserializeOwned>(
+        &self,
+        request: &ApiRequest,
+        response: &RawResponse,
+    ) -> Result<T, ApiCallError> {
+        response.parse().inspect_err(|e| {
+            if let ApiCallError::ResponseParseError { source, .. } = e {
+                self.notify(|o| o.on_parse_failure(&request.info(&response.url, response.attempt), source));
             }
-            Err(e) => Err(ApiCallError::ErrorFormingUrl(e)),
+        })
+    }
+
+    fn notify(&self, event: impl Fn(&dyn ClientObserver)) {
+        for observer in &self.observers {
+            event(observer.as_ref());
+        }
+    }
+}
+
+/// One call to the API, before an API key is added.
+pub(crate) struct ApiRequest {
+    /// Short name of the endpoint, used in logs, spans and observer events.
+    pub(crate) endpoint: &'static str,
+    pub(crate) method: Method,
+    /// The host serving the endpoint.
+    pub(crate) host: ApiHost,
+    /// Path of the endpoint on its host, without query parameters.
+    pub(crate) path: String,
+    /// What the call is about, usually the query's [Display](std::fmt::Display) form.
+    pub(crate) subject: String,
+    /// Query parameters, other than the API key.
+    pub(crate) params: QueryParameters,
+    /// A JSON encoded body.
+    pub(crate) body: Option<Bytes>,
+}
+
+impl ApiRequest {
+    /// A `GET` of `endpoint` with only `params`, for endpoints that aren't about a [`Query`].
+    pub(crate) fn new(endpoint: Endpoint, subject: &str, params: QueryParameters) -> Self {
+        ApiRequest {
+            endpoint: endpoint.name,
+            method: Method::GET,
+            host: endpoint.host,
+            path: endpoint.path.to_string(),
+            subject: subject.to_string(),
+            params,
+            body: None,
+        }
+    }
+
+    /// A `GET` of the resource `id` of the collection at `endpoint`, named `name` in logs.  The id must be
+    /// alphanumeric, like those handed out by the API, so that it can't change the request target, e.g. with `../x`.
+    pub(crate) fn resource(endpoint: Endpoint, name: &'static str, id: &str) -> Result<Self, ApiCallError> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(ApiCallError::InvalidResourceId(id.to_string()));
         }
+        let mut request = ApiRequest::new(endpoint, id, Vec::new());
+        request.endpoint = name;
+        request.path = format!("{}/{id}", endpoint.path);
+        Ok(request)
+    }
+
+    /// Sends `body`, encoded as JSON, with `method` instead of a `GET`.
+    pub(crate) fn with_json_body(mut self, method: Method, body: &impl Serialize) -> Result<Self, ApiCallError> {
+        self.method = method;
+        self.body = Some(Bytes::from(serde_json::to_vec(body)?));
+        Ok(self)
+    }
+
+    fn info<'a>(&'a self, masked_url: &'a str, attempt: u32) -> RequestInfo<'a> {
+        RequestInfo {
+            endpoint: self.endpoint,
+            url: masked_url,
+            query: &self.subject,
+            attempt,
+        }
+    }
+}
+
+/// A successful response, with its body read but not parsed.  Useful to keep the exact payload, e.g. to archive it,
+/// while still getting the typed model with [`parse`](RawResponse::parse).
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub(crate) url: String,
+    pub(crate) attempt: u32,
+    pub(crate) elapsed: Duration,
+    pub(crate) response: Response<Bytes>,
+}
+
+impl RawResponse {
+    /// The HTTP status, always a success (2xx).
+    pub fn status(&self) -> StatusCode {
+        self.response.status()
+    }
+
+    /// The response headers.
+    pub fn headers(&self) -> &HeaderMap {
+        self.response.headers()
     }
 
-    async fn handle_200_response(&self, response_body: Response<Incoming>) -> Result<CurrentWeather, ApiCallError> {
-        let body = response_body_as_str(response_body).await?;
+    /// The response body, as received.
+    pub fn body(&self) -> &Bytes {
+        self.response.body()
+    }
+
+    /// The request URL with the API key masked.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Time from handing the request to the transport to reading the whole response, see
+    /// [`ClientObserver::on_response`].
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Parses the body into the endpoint's model, e.g. a [`CurrentWeather`] for
+    /// [`fetch_weather_raw`](Client::fetch_weather_raw).
+    ///
+    /// # Errors
+    /// [`ApiCallError::ResponseEncodingError`] if the body isn't utf-8 or [`ApiCallError::ResponseParseError`] if it
+    /// isn't a `T`.
+    pub fn parse<T: DeserializeOwned>(&self) -> Result<T, ApiCallError> {
+        let body = match std::str::from_utf8(self.body()) {
+            Ok(str) => Ok(str),
+            Err(e) => Err(ApiCallError::ResponseEncodingError(e)),
+        }?;
 
         trace!("Response: {body}");
-        match serde_yaml::from_str::<CurrentWeather>(&body) {
-            Ok(weather) => Ok(weather),
-            Err(e) => Err(ApiCallError::ResponseParseError {
-                source: e,
-                content: body,
-            }),
+        serde_yaml::from_str::<T>(body).map_err(|e| ApiCallError::ResponseParseError {
+            source: e,
+            content: body.to_string(),
+        })
+    }
+}
+
+fn url_for(base_url: &str, request: &ApiRequest, api_key: &str) -> Result<Url, ApiCallError> {
+    match Url::parse(&format!("{}{}", base_url.trim_end_matches('/'), request.path)) {
+        Ok(mut url) => {
+            {
+                let mut query_pairs = url.query_pairs_mut();
+                for p in &request.params {
+                    query_pairs.append_pair(p.0, &p.1);
+                }
+                query_pairs.append_pair("appid", api_key);
+            }
+            Ok(url)
         }
+        Err(e) => Err(ApiCallError::ErrorFormingUrl(e)),
     }
+}
 
-    async fn handle_non_200_response(&self, response_body: Response<Incoming>, sc: &StatusCode) -> ApiCallError {
-        let rb = match response_body_as_str(response_body).await {
-            Ok(rb) => rb,
-            Err(error) => format!("Error obtaining response body {error:?}"),
-        };
-        ApiCallError::InvalidResponsStatus { status: *sc, body: rb }
+/// The URL of the collection a request changes: its own path for a `POST`, which adds to it, otherwise its parent,
+/// e.g. `/agro/1.0/polygons` for a `DELETE` of `/agro/1.0/polygons/{id}`.
+fn collection_url(base_url: &str, request: &ApiRequest) -> Result<Url, ApiCallError> {
+    let path = match request.method {
+        Method::POST => request.path.as_str(),
+        _ => request.path.rsplit_once('/').map_or("", |(parent, _)| parent),
+    };
+    Url::parse(&format!("{}{path}", base_url.trim_end_matches('/'))).map_err(ApiCallError::ErrorFormingUrl)
+}
+
+/// The wait asked for by a `429 Too Many Requests` response, in seconds.
+fn retry_after(response: &Response<Bytes>) -> Option<Duration> {
+    if response.status() != StatusCode::TOO_MANY_REQUESTS {
+        return None;
     }
+    let seconds = response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
 }
 
-async fn response_body_as_str(response_body: hyper::Response<Incoming>) -> Result<String, ApiCallError> {
-    let buf = match response_body.collect().await {
-        Ok(ok) => Ok(ok.to_bytes()),
-        Err(e) => Err(ApiCallError::ResponseReadError(e)),
-    }?;
-    match std::str::from_utf8(&buf) {
-        Ok(str) => Ok(str.to_owned()),
-        Err(e) => Err(ApiCallError::ResponseEncodingError(e)),
+fn unsuccessful_response(response: &Response<Bytes>) -> ApiCallError {
+    let body = match std::str::from_utf8(response.body()) {
+        Ok(body) => body.to_string(),
+        Err(error) => format!("Error obtaining response body {error:?}"),
+    };
+    ApiCallError::InvalidResponsStatus {
+        status: response.status(),
+        body,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_join_the_host_base_url_and_the_endpoint_path() {
+        let request = ApiRequest {
+            endpoint: "weather",
+            method: Method::GET,
+            host: ApiHost::Api,
+            path: "/data/2.5/weather".to_string(),
+            subject: "Paris".to_string(),
+            params: vec![("q", "Paris,FR".to_string())],
+            body: None,
+        };
+
+        assert_eq!(
+            url_for("https://api.openweathermap.org", &request, "KEY")
+                .unwrap()
+                .as_str(),
+            "https://api.openweathermap.org/data/2.5/weather?q=Paris%2CFR&appid=KEY"
+        );
+        assert_eq!(
+            url_for("https://proxy.example.com/owm/", &request, "KEY")
+                .unwrap()
+                .as_str(),
+            "https://proxy.example.com/owm/data/2.5/weather?q=Paris%2CFR&appid=KEY"
+        );
+    }
+
+    #[test]
+    fn resource_ids_must_be_alphanumeric() {
+        let collection = Endpoint::new("things", ApiHost::Api, "/data/3.0/things");
+
+        let request = ApiRequest::resource(collection, "thing", "583436dd9643a9000196b8d6").unwrap();
+        assert_eq!(request.path, "/data/3.0/things/583436dd9643a9000196b8d6");
+
+        for id in ["", "..", "../stations", "a/b", "a?b=c", "a#b", "a%2Fb"] {
+            assert!(
+                matches!(
+                    ApiRequest::resource(collection, "thing", id),
+                    Err(ApiCallError::InvalidResourceId(_))
+                ),
+                "{id:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn changes_evict_the_collection_they_belong_to() {
+        let mut request = ApiRequest::new(
+            Endpoint::new("agro_polygons", ApiHost::Agro, "/agro/1.0/polygons"),
+            "North field",
+            Vec::new(),
+        );
+        request.method = Method::POST;
+        assert_eq!(
+            collection_url("https://api.agromonitoring.com/", &request)
+                .unwrap()
+                .as_str(),
+            "https://api.agromonitoring.com/agro/1.0/polygons"
+        );
+
+        request.method = Method::DELETE;
+        request.path.push_str("/5aaa8052cbbbb5000b73ff66");
+        assert_eq!(
+            collection_url("https://api.agromonitoring.com", &request)
+                .unwrap()
+                .as_str(),
+            "https://api.agromonitoring.com/agro/1.0/polygons"
+        );
     }
 }
@@ -1,10 +1,13 @@
 use std::{
     fmt::{Display, Formatter, Result},
+    path::PathBuf,
     str::Utf8Error,
 };
 use thiserror::Error;
 use url::ParseError;
 
+use crate::transport::TransportError;
+
 #[derive(Debug, Error)]
 pub enum ClientError {
     #[error("error calling API")]
@@ -15,6 +18,9 @@ pub enum ClientError {
 
     #[error("error creating https client")]
     HyperRustlsError(#[from] std::io::Error),
+
+    #[error("error opening cassette")]
+    CassetteError(#[from] CassetteError),
 }
 
 #[derive(Debug, Error)]
@@ -25,14 +31,17 @@ pub enum ApiCallError {
     #[error("error building URI")]
     ErrorFormingUrl(#[from] ParseError),
 
+    #[error("error building request")]
+    ErrorFormingRequest(#[from] hyper::http::Error),
+
+    #[error("error encoding request body.  Error: {0:?}")]
+    RequestEncodingError(#[from] serde_json::Error),
+
     #[error("unexpected response. Status: {status:?}, Body: {body:?}")]
     InvalidResponsStatus { status: hyper::StatusCode, body: String },
 
     #[error("API call to {url:?} failed. Error: {error:?}")]
-    HttpError {
-        error: hyper_util::client::legacy::Error,
-        url: String,
-    },
+    HttpError { error: TransportError, url: String },
 
     #[error("Response body not utf-8 encoded.  Error: {0:?}")]
     ResponseEncodingError(#[from] Utf8Error),
@@ -42,6 +51,67 @@ pub enum ApiCallError {
 
     #[error("Error parsing response body.  Error: {source:?}. Content: {content}")]
     ResponseParseError { source: serde_yaml::Error, content: String },
+
+    #[error("Every api key in the pool is out of rotation")]
+    NoApiKeyAvailable,
+
+    #[error("{0:?} is not a valid id, ids are alphanumeric")]
+    InvalidResourceId(String),
+
+    #[error("{0}")]
+    InvalidTile(#[from] TileError),
+
+    #[error("{0:?} has no hours in it")]
+    EmptyHistoryRange(crate::history::HistoryRange),
+
+    #[error("No recorded response to {method} {url}")]
+    CassetteMiss { method: String, url: String },
+
+    #[error("Error using cassette.  Error: {0}")]
+    CassetteError(#[from] CassetteError),
+}
+
+/// Why a [`Query`](crate::Query) would be rejected by the API, see [`Query::validate`](crate::Query::validate).
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum QueryValidationError {
+    #[error("latitude {0} is not between -90 and 90")]
+    LatitudeOutOfRange(f64),
+
+    #[error("longitude {0} is not between -180 and 180")]
+    LongitudeOutOfRange(f64),
+
+    #[error("{0:?} is not an ISO 3166-1 alpha-2 country code")]
+    UnknownCountryCode(String),
+
+    /// Only raised for the countries whose subdivisions are bundled: the US, Canada and Australia.  Other
+    /// countries' subdivisions are not checked.
+    #[error("{subdivision:?} is not an ISO 3166-2 subdivision of {country_code} (only US, CA and AU subdivisions are checked)")]
+    UnknownSubdivision { country_code: String, subdivision: String },
+
+    #[error("city name must be non-blank")]
+    BlankCityName,
+
+    #[error("zip code must be non-blank")]
+    BlankZipCode,
+
+    #[error("city id must be non-zero")]
+    ZeroCityId,
+}
+
+/// Why a [`WeatherProvider`](crate::provider::WeatherProvider) produced no observation.
+#[derive(Debug, Error)]
+pub enum ProviderError {
+    #[error("OpenWeatherMap call failed.  Error: {0}")]
+    Api(#[from] ApiCallError),
+
+    #[error("{provider} can't look up {location}")]
+    UnsupportedLocation { provider: String, location: String },
+
+    #[error("{provider} is unavailable: {message}")]
+    Unavailable { provider: String, message: String },
+
+    #[error("every provider failed: {}", .0.iter().map(|(provider, e)| format!("{provider}: {e}")).collect::<Vec<_>>().join("; "))]
+    AllFailed(Vec<(String, ProviderError)>),
 }
 
 #[derive(Debug)]
@@ -54,3 +124,73 @@ impl Display for InvalidOptionsError {
     }
 }
 impl std::error::Error for InvalidOptionsError {}
+
+#[derive(Debug)]
+pub struct QueryParseError {
+    pub input: String,
+    pub message: String,
+}
+impl Display for QueryParseError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "unable to parse query {:?}: {}", self.input, self.message)
+    }
+}
+impl std::error::Error for QueryParseError {}
+
+#[derive(Debug)]
+pub struct CassetteError {
+    pub path: PathBuf,
+    pub message: String,
+}
+impl Display for CassetteError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "cassette {}: {}", self.path.display(), self.message)
+    }
+}
+impl std::error::Error for CassetteError {}
+
+#[derive(Debug)]
+pub struct CityListError {
+    pub path: PathBuf,
+    pub message: String,
+}
+impl Display for CityListError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "city list {}: {}", self.path.display(), self.message)
+    }
+}
+impl std::error::Error for CityListError {}
+
+#[derive(Debug)]
+pub struct SummaryTemplateError {
+    pub template: String,
+    pub message: String,
+}
+impl Display for SummaryTemplateError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "summary template {:?}: {}", self.template, self.message)
+    }
+}
+impl std::error::Error for SummaryTemplateError {}
+
+#[derive(Debug)]
+pub struct RegionError {
+    pub message: String,
+}
+impl Display for RegionError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "invalid region: {}", self.message)
+    }
+}
+impl std::error::Error for RegionError {}
+
+#[derive(Debug)]
+pub struct TileError {
+    pub message: String,
+}
+impl Display for TileError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "invalid tile: {}", self.message)
+    }
+}
+impl std::error::Error for TileError {}
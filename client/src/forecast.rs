@@ -0,0 +1,265 @@
+//! Daily forecasts up to 16 days ahead from the [Daily Forecast API](https://openweathermap.org/forecast16) and hourly
+//! forecasts 4 days ahead from the [Hourly Forecast API](https://openweathermap.org/api/hourly-forecast).  Both
+//! require a paid subscription.
+
+use serde::Deserialize;
+
+use crate::{
+    error::ApiCallError,
+    hosts::{ApiHost, Endpoint},
+    models::{Clouds, Coord, Main, PrecipVolume, Weather, Wind},
+    Client, Query, RawResponse,
+};
+
+/// Api docs are here <https://openweathermap.org/forecast16>
+const V25_FORECAST_DAILY: Endpoint = Endpoint::new("forecast_daily", ApiHost::Api, "/data/2.5/forecast/daily");
+
+/// Api docs are here <https://openweathermap.org/api/hourly-forecast>
+const V25_FORECAST_HOURLY: Endpoint = Endpoint::new("forecast_hourly", ApiHost::Pro, "/data/2.5/forecast/hourly");
+
+/// The location a forecast is for.
+#[derive(Debug, Deserialize)]
+pub struct ForecastCity {
+    /// City ID
+    pub id: u64,
+    /// City name
+    pub name: String,
+    /// See [Coord]
+    pub coord: Coord,
+    /// Country code (GB, JP etc.)
+    pub country: String,
+    /// City population
+    pub population: Option<u64>,
+    /// Shift in seconds from UTC
+    pub timezone: Option<i64>,
+    /// Sunrise time, unix, UTC.  Only present in hourly forecasts.
+    pub sunrise: Option<i64>,
+    /// Sunset time, unix, UTC.  Only present in hourly forecasts.
+    pub sunset: Option<i64>,
+}
+
+/// Temperatures over the parts of a day.  Unit Default: Kelvin, Metric: Celsius, Imperial: Fahrenheit.
+#[derive(Debug, Deserialize)]
+pub struct DayTemperatures {
+    /// Day temperature
+    pub day: f64,
+    /// Min daily temperature
+    pub min: f64,
+    /// Max daily temperature
+    pub max: f64,
+    /// Night temperature
+    pub night: f64,
+    /// Evening temperature
+    pub eve: f64,
+    /// Morning temperature
+    pub morn: f64,
+}
+
+/// Temperatures as perceived by humans over the parts of a day.  Unit Default: Kelvin, Metric: Celsius, Imperial:
+/// Fahrenheit.
+#[derive(Debug, Deserialize)]
+pub struct DayPartTemperatures {
+    /// Day temperature
+    pub day: f64,
+    /// Night temperature
+    pub night: f64,
+    /// Evening temperature
+    pub eve: f64,
+    /// Morning temperature
+    pub morn: f64,
+}
+
+/// Forecast for one day.
+#[derive(Debug, Deserialize)]
+pub struct DailyForecast {
+    /// Time of the forecast, unix, UTC (in seconds)
+    pub dt: i64,
+    /// Sunrise time, unix, UTC
+    pub sunrise: Option<i64>,
+    /// Sunset time, unix, UTC
+    pub sunset: Option<i64>,
+    /// See [`DayTemperatures`]
+    pub temp: DayTemperatures,
+    /// See [`DayPartTemperatures`]
+    pub feels_like: DayPartTemperatures,
+    /// Atmospheric pressure on the sea level, hPa
+    pub pressure: f64,
+    /// Humidity, %
+    pub humidity: f64,
+    /// Seems to generally be a collection of one
+    pub weather: Vec<Weather>,
+    /// Wind speed. Unit Default: meter/sec, Metric: meter/sec, Imperial: miles/hour.
+    pub speed: f64,
+    /// Wind direction, degrees (meteorological)
+    pub deg: f64,
+    /// Wind gust. Unit Default: meter/sec, Metric: meter/sec, Imperial: miles/hour
+    pub gust: Option<f64>,
+    /// Cloudiness, %
+    pub clouds: f64,
+    /// Probability of precipitation, 0 to 1
+    pub pop: Option<f64>,
+    /// Rain volume over the day, mm
+    pub rain: Option<f64>,
+    /// Snow volume over the day, mm
+    pub snow: Option<f64>,
+}
+
+/// Response from the Daily Forecast API.
+#[derive(Debug, Deserialize)]
+pub struct DailyForecastResponse {
+    /// See [`ForecastCity`]
+    pub city: ForecastCity,
+    /// Internal parameter
+    pub cod: Option<String>,
+    /// Internal parameter
+    pub message: Option<f64>,
+    /// Number of days in `list`
+    pub cnt: u64,
+    /// The daily forecasts, soonest first
+    pub list: Vec<DailyForecast>,
+}
+
+/// Forecast for one hour.
+#[derive(Debug, Deserialize)]
+pub struct HourlyForecast {
+    /// Time of the forecast, unix, UTC (in seconds)
+    pub dt: i64,
+    /// See [Main]
+    pub main: Main,
+    /// Seems to generally be a collection of one
+    pub weather: Vec<Weather>,
+    /// See [Clouds]
+    pub clouds: Clouds,
+    /// See [Wind]
+    pub wind: Wind,
+    /// Visibility, meter. The maximum value of the visibility is 10km
+    pub visibility: Option<u16>,
+    /// Probability of precipitation, 0 to 1
+    pub pop: Option<f64>,
+    /// Rain volume
+    pub rain: Option<PrecipVolume>,
+    /// Snow volume
+    pub snow: Option<PrecipVolume>,
+    /// Time of the forecast, formatted `YYYY-MM-DD hh:mm:ss`, UTC
+    pub dt_txt: Option<String>,
+}
+
+/// Response from the Hourly Forecast API.
+#[derive(Debug, Deserialize)]
+pub struct HourlyForecastResponse {
+    /// Internal parameter
+    pub cod: Option<String>,
+    /// Internal parameter
+    pub message: Option<f64>,
+    /// Number of hours in `list`
+    pub cnt: u64,
+    /// The hourly forecasts, soonest first
+    pub list: Vec<HourlyForecast>,
+    /// See [`ForecastCity`]
+    pub city: ForecastCity,
+}
+
+impl Client {
+    /// Fetch the daily forecast for the next `days` days (at most 16).
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_daily_forecast(
+        &self,
+        query: &dyn Query,
+        days: u8,
+    ) -> Result<DailyForecastResponse, ApiCallError> {
+        let params = vec![("cnt", days.to_string())];
+        self.get(V25_FORECAST_DAILY, query, params).await
+    }
+
+    /// Fetch the hourly forecast for the next 4 days, optionally limited to the first `hours` hours.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_hourly_forecast(
+        &self,
+        query: &dyn Query,
+        hours: Option<u8>,
+    ) -> Result<HourlyForecastResponse, ApiCallError> {
+        let params = hours.map(|hours| ("cnt", hours.to_string())).into_iter().collect();
+        self.get(V25_FORECAST_HOURLY, query, params).await
+    }
+
+    /// Fetch the daily forecast like [`fetch_daily_forecast`](Client::fetch_daily_forecast) without parsing it.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_daily_forecast_raw(&self, query: &dyn Query, days: u8) -> Result<RawResponse, ApiCallError> {
+        let params = vec![("cnt", days.to_string())];
+        self.get_raw(V25_FORECAST_DAILY, query, params).await
+    }
+
+    /// Fetch the hourly forecast like [`fetch_hourly_forecast`](Client::fetch_hourly_forecast) without parsing it.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_hourly_forecast_raw(
+        &self,
+        query: &dyn Query,
+        hours: Option<u8>,
+    ) -> Result<RawResponse, ApiCallError> {
+        let params = hours.map(|hours| ("cnt", hours.to_string())).into_iter().collect();
+        self.get_raw(V25_FORECAST_HOURLY, query, params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CITY: &str = r#"{"id":2643743,"name":"London","coord":{"lat":51.5073,"lon":-0.1277},"country":"GB",
+        "population":1000000,"timezone":0,"sunrise":1684468262,"sunset":1684524626}"#;
+
+    #[test]
+    fn parse_daily_forecast() {
+        let parsed: DailyForecastResponse = serde_yaml::from_str(&format!(
+            r#"{{"city":{CITY},"cod":"200","message":0.0582,"cnt":1,"list":[
+                {{"dt":1684494000,"sunrise":1684468262,"sunset":1684524626,
+                 "temp":{{"day":18.02,"min":9.93,"max":19.42,"night":12.4,"eve":17.03,"morn":10.56}},
+                 "feels_like":{{"day":17.37,"night":11.84,"eve":16.52,"morn":9.86}},
+                 "pressure":1024,"humidity":54,
+                 "weather":[{{"id":500,"main":"Rain","description":"light rain","icon":"10d"}}],
+                 "speed":4.14,"deg":51,"gust":7.81,"clouds":59,"pop":0.36,"rain":0.4}}]}}"#
+        ))
+        .unwrap();
+
+        assert_eq!(parsed.city.name, "London");
+        assert_eq!(parsed.city.sunrise, Some(1_684_468_262));
+        assert_eq!(parsed.cnt, 1);
+        let day = &parsed.list[0];
+        assert!((day.temp.max - 19.42).abs() < f64::EPSILON);
+        assert!((day.feels_like.morn - 9.86).abs() < f64::EPSILON);
+        assert_eq!(day.pop, Some(0.36));
+        assert_eq!(day.rain, Some(0.4));
+        assert_eq!(day.snow, None);
+        assert_eq!(day.sunset, Some(1_684_524_626));
+    }
+
+    #[test]
+    fn parse_hourly_forecast() {
+        let parsed: HourlyForecastResponse = serde_yaml::from_str(&format!(
+            r#"{{"cod":"200","message":0,"cnt":1,"list":[
+                {{"dt":1684497600,
+                 "main":{{"temp":17.26,"feels_like":16.6,"temp_min":17.26,"temp_max":17.7,"pressure":1024,"sea_level":1024,"grnd_level":1020,"humidity":57}},
+                 "weather":[{{"id":803,"main":"Clouds","description":"broken clouds","icon":"04d"}}],
+                 "clouds":{{"all":75}},"wind":{{"speed":3.76,"deg":46,"gust":5.08}},"visibility":10000,"pop":0.2,
+                 "rain":{{"1h":0.15}},"dt_txt":"2023-05-19 12:00:00"}}],
+                "city":{CITY}}}"#
+        ))
+        .unwrap();
+
+        assert_eq!(parsed.list.len(), 1);
+        let hour = &parsed.list[0];
+        assert_eq!(hour.dt, 1_684_497_600);
+        assert_eq!(hour.pop, Some(0.2));
+        assert_eq!(hour.rain.as_ref().and_then(|r| r.one_hour), Some(0.15));
+        assert_eq!(hour.dt_txt.as_deref(), Some("2023-05-19 12:00:00"));
+        assert_eq!(parsed.city.country, "GB");
+    }
+}
@@ -0,0 +1,87 @@
+//! Distances and directions between [coordinates](Coord), on a spherical Earth.
+//!
+//! For a [`Coord`] query, the API answers with the reading of the nearest station, whose coordinates may be some
+//! way off those asked about:
+//!
+//! ```
+//! use openweathermap_client::{geo, models::Coord};
+//!
+//! let asked = Coord::new(45.1885, 5.7245);
+//! let station = Coord::new(45.3622, 5.3294);
+//!
+//! assert!((geo::haversine_km(&asked, &station) - 36.5).abs() < 0.1);
+//! assert_eq!(geo::bearing(&asked, &station).round(), 302.0);
+//! ```
+
+use crate::models::Coord;
+
+/// Mean radius of the Earth, km.
+pub const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Great-circle distance between two coordinates, km.
+pub fn haversine_km(a: &Coord, b: &Coord) -> f64 {
+    let (lat_a, lat_b) = (a.lat.to_radians(), b.lat.to_radians());
+    let half_lat = (b.lat - a.lat).to_radians() / 2.0;
+    let half_lon = (b.lon - a.lon).to_radians() / 2.0;
+    let h = half_lat.sin().powi(2) + lat_a.cos() * lat_b.cos() * half_lon.sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().min(1.0).asin()
+}
+
+/// Initial bearing of the great circle from `from` to `to`, degrees clockwise from north, 0 up to 360.  0 when the
+/// coordinates are the same.
+pub fn bearing(from: &Coord, to: &Coord) -> f64 {
+    let (lat_from, lat_to) = (from.lat.to_radians(), to.lat.to_radians());
+    let delta_lon = (to.lon - from.lon).to_radians();
+    let y = delta_lon.sin() * lat_to.cos();
+    let x = lat_from.cos() * lat_to.sin() - lat_from.sin() * lat_to.cos() * delta_lon.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{actual} is not {expected} ± {tolerance}"
+        );
+    }
+
+    #[test]
+    fn distances_are_along_the_great_circle() {
+        let paris = Coord::new(48.8566, 2.3522);
+        let london = Coord::new(51.5074, -0.1278);
+
+        assert_close(haversine_km(&paris, &london), 343.5, 0.5);
+        assert_close(haversine_km(&london, &paris), haversine_km(&paris, &london), 1e-9);
+        assert_close(haversine_km(&paris, &paris), 0.0, 1e-9);
+        assert_close(
+            haversine_km(&Coord::new(0.0, -90.0), &Coord::new(0.0, 90.0)),
+            20_015.1,
+            0.1,
+        );
+    }
+
+    #[test]
+    fn bearings_are_clockwise_from_north() {
+        let origin = Coord::new(0.0, 0.0);
+
+        assert_close(bearing(&origin, &Coord::new(1.0, 0.0)), 0.0, 1e-9);
+        assert_close(bearing(&origin, &Coord::new(0.0, 1.0)), 90.0, 1e-9);
+        assert_close(bearing(&origin, &Coord::new(-1.0, 0.0)), 180.0, 1e-9);
+        assert_close(bearing(&origin, &Coord::new(0.0, -1.0)), 270.0, 1e-9);
+        assert_close(bearing(&origin, &origin), 0.0, 1e-9);
+    }
+
+    #[test]
+    fn bearings_across_the_antimeridian_take_the_short_way() {
+        let fiji = Coord::new(-17.7, 179.0);
+        let samoa = Coord::new(-13.8, -172.1);
+
+        let heading = bearing(&fiji, &samoa);
+
+        assert!((0.0..90.0).contains(&heading), "{heading}");
+        assert!(haversine_km(&fiji, &samoa) < 1100.0);
+    }
+}
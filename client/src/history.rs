@@ -0,0 +1,284 @@
+//! Hourly historical weather from the [History API](https://openweathermap.org/history).  Requires a paid
+//! subscription.
+
+use serde::Deserialize;
+
+use crate::{
+    error::ApiCallError,
+    hosts::{ApiHost, Endpoint},
+    models::{Clouds, Extra, Main, PrecipVolume, Weather, Wind},
+    query::QueryParameters,
+    Client, Query,
+};
+
+/// Api docs are here <https://openweathermap.org/history>
+const V25_HISTORY: Endpoint = Endpoint::new("history", ApiHost::History, "/data/2.5/history/city");
+
+/// The API returns at most one week of hourly readings per call.
+const SECONDS_PER_PAGE: i64 = 7 * 24 * 60 * 60;
+
+/// The number of hourly readings in one page.
+const HOURS_PER_PAGE: u32 = 7 * 24;
+
+/// The span of time to request history for.  Times are unix, UTC (in seconds).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryRange {
+    /// Every hour from `start` up to and including `end`.
+    Between {
+        /// First hour of the range
+        start: i64,
+        /// End of the range, inclusive
+        end: i64,
+    },
+    /// `count` hours starting at `start`.
+    Count {
+        /// First hour of the range
+        start: i64,
+        /// Number of hourly readings
+        count: u32,
+    },
+}
+
+impl HistoryRange {
+    /// Splits the range into consecutive, non-overlapping ranges that each fit within the one-week-per-call limit.
+    pub fn pages(&self) -> Vec<HistoryRange> {
+        match *self {
+            HistoryRange::Between { start, end } => {
+                let mut pages = Vec::new();
+                let mut page_start = start;
+                while page_start <= end {
+                    let page_end = end.min(page_start.saturating_add(SECONDS_PER_PAGE));
+                    pages.push(HistoryRange::Between {
+                        start: page_start,
+                        end: page_end,
+                    });
+                    // `end` is inclusive, the next page must not read the hour at `page_end` again
+                    page_start = page_end.saturating_add(1);
+                }
+                pages
+            }
+            HistoryRange::Count { start, count } => {
+                let mut pages = Vec::new();
+                let mut remaining = count;
+                let mut page_start = start;
+                while remaining > 0 {
+                    let page_count = remaining.min(HOURS_PER_PAGE);
+                    pages.push(HistoryRange::Count {
+                        start: page_start,
+                        count: page_count,
+                    });
+                    remaining -= page_count;
+                    page_start = page_start.saturating_add(i64::from(page_count) * 60 * 60);
+                }
+                pages
+            }
+        }
+    }
+
+    fn query_params(&self) -> QueryParameters {
+        match self {
+            HistoryRange::Between { start, end } => vec![
+                ("type", "hour".to_string()),
+                ("start", start.to_string()),
+                ("end", end.to_string()),
+            ],
+            HistoryRange::Count { start, count } => vec![
+                ("type", "hour".to_string()),
+                ("start", start.to_string()),
+                ("cnt", count.to_string()),
+            ],
+        }
+    }
+}
+
+/// Response from the History API.
+///
+/// See their API response documentation [here](https://openweathermap.org/history#parameter).
+#[derive(Debug, Deserialize)]
+pub struct HistoryResponse {
+    /// Internal parameter
+    pub message: Option<String>,
+    /// Internal parameter
+    pub cod: Option<String>,
+    /// City ID
+    pub city_id: u64,
+    /// Internal parameter
+    pub calctime: Option<f64>,
+    /// Number of hourly readings in `list`
+    pub cnt: u64,
+    /// The hourly readings, oldest first
+    pub list: Vec<HistoryEntry>,
+}
+
+/// One hour of historical weather.
+#[derive(Debug, Deserialize)]
+pub struct HistoryEntry {
+    /// Time of data calculation, unix, UTC (in seconds)
+    pub dt: i64,
+    /// See [Main]
+    pub main: Main,
+    /// See [Wind]
+    pub wind: Wind,
+    /// See [Clouds]
+    pub clouds: Option<Clouds>,
+    /// Seems to generally be a collection of one
+    pub weather: Vec<Weather>,
+    /// Rain volume
+    pub rain: Option<PrecipVolume>,
+    /// Snow volume
+    pub snow: Option<PrecipVolume>,
+    /// See [Extra]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Client {
+    /// Fetch hourly historical weather for a [`City`](crate::models::City), [`CityId`](crate::models::CityId) or
+    /// [`Coord`](crate::models::Coord) query.  Ranges longer than the API's one-week-per-call limit are fetched one
+    /// week at a time and returned as a single response.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].  Fails as soon as any page fails, and without calling
+    /// the API when the range is empty.
+    pub async fn fetch_history(
+        &self,
+        query: &dyn Query,
+        range: &HistoryRange,
+    ) -> Result<HistoryResponse, ApiCallError> {
+        let mut pages = range.pages().into_iter();
+        let first_page = pages.next().ok_or(ApiCallError::EmptyHistoryRange(*range))?;
+
+        let mut merged: HistoryResponse = self.get(V25_HISTORY, query, first_page.query_params()).await?;
+        for page in pages {
+            let response: HistoryResponse = self.get(V25_HISTORY, query, page.query_params()).await?;
+            merged.list.extend(response.list);
+            merged.cnt += response.cnt;
+            merged.calctime = response.calctime;
+            merged.message = response.message;
+        }
+        Ok(merged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    #[test]
+    fn short_ranges_are_a_single_page() {
+        let range = HistoryRange::Between {
+            start: 1_578_384_000,
+            end: 1_578_384_000 + DAY,
+        };
+        assert_eq!(range.pages(), vec![range]);
+
+        let range = HistoryRange::Count {
+            start: 1_578_384_000,
+            count: 24,
+        };
+        assert_eq!(range.pages(), vec![range]);
+    }
+
+    #[test]
+    fn long_between_ranges_are_split_into_weeks() {
+        let start = 1_578_384_000;
+        let pages = HistoryRange::Between {
+            start,
+            end: start + 16 * DAY,
+        }
+        .pages();
+
+        assert_eq!(
+            pages,
+            vec![
+                HistoryRange::Between {
+                    start,
+                    end: start + 7 * DAY
+                },
+                HistoryRange::Between {
+                    start: start + 7 * DAY + 1,
+                    end: start + 14 * DAY + 1
+                },
+                HistoryRange::Between {
+                    start: start + 14 * DAY + 2,
+                    end: start + 16 * DAY
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn between_ranges_include_their_end() {
+        let range = HistoryRange::Between { start: 10, end: 10 };
+        assert_eq!(range.pages(), vec![range]);
+
+        let start = 1_578_384_000;
+        let pages = HistoryRange::Between {
+            start,
+            end: start + 7 * DAY + 1,
+        }
+        .pages();
+        assert_eq!(
+            pages.last(),
+            Some(&HistoryRange::Between {
+                start: start + 7 * DAY + 1,
+                end: start + 7 * DAY + 1
+            })
+        );
+    }
+
+    #[test]
+    fn long_count_ranges_are_split_into_weeks() {
+        let start = 1_578_384_000;
+        let pages = HistoryRange::Count { start, count: 200 }.pages();
+
+        assert_eq!(
+            pages,
+            vec![
+                HistoryRange::Count { start, count: 168 },
+                HistoryRange::Count {
+                    start: start + 7 * DAY,
+                    count: 32
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_ranges_have_no_pages() {
+        assert!(HistoryRange::Between { start: 10, end: 5 }.pages().is_empty());
+        assert!(HistoryRange::Count { start: 10, count: 0 }.pages().is_empty());
+    }
+
+    #[test]
+    fn parse_response() {
+        let parsed: HistoryResponse = serde_yaml::from_str(
+            r#"{"message":"Count: 1","cod":"200","city_id":4298960,"calctime":0.00297316,"cnt":1,"list":[
+                {"dt":1578384000,"main":{"temp":275.45,"feels_like":271.7,"pressure":1014,"humidity":74,"temp_min":274.26,"temp_max":276.48},
+                 "wind":{"speed":2.16,"deg":87},"clouds":{"all":90},
+                 "weather":[{"id":501,"main":"Rain","description":"moderate rain","icon":"10n"}],"rain":{"1h":0.9}}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(parsed.city_id, 4_298_960);
+        assert_eq!(parsed.cod.as_deref(), Some("200"));
+        assert_eq!(parsed.list.len(), 1);
+        assert_eq!(parsed.list[0].dt, 1_578_384_000);
+        assert!((parsed.list[0].main.temp - 275.45).abs() < f64::EPSILON);
+        assert_eq!(parsed.list[0].rain.as_ref().and_then(|r| r.one_hour), Some(0.9));
+    }
+
+    #[test]
+    fn parse_entry_without_clouds() {
+        let entry: HistoryEntry = serde_yaml::from_str(
+            r#"{"dt":1578384000,"main":{"temp":275.45,"feels_like":271.7,"pressure":1014,"humidity":74,"temp_min":274.26,"temp_max":276.48},
+                "wind":{"speed":2.16},"weather":[],"visibility":10000}"#,
+        )
+        .unwrap();
+
+        assert!(entry.clouds.is_none());
+        assert_eq!(entry.extra.get("visibility"), Some(&serde_json::json!(10000)));
+    }
+}
@@ -0,0 +1,68 @@
+//! The API is spread across several hosts.  Each endpoint is a path on one of them, and the base URL of any host can
+//! be overridden through [`ClientOptions::hosts`](crate::ClientOptions::hosts).
+
+use serde::Deserialize;
+use std::fmt;
+
+/// The hosts that serve the API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiHost {
+    /// `api.openweathermap.org`, the free and most of the paid endpoints
+    Api,
+    /// `pro.openweathermap.org`, endpoints only available to paid subscriptions
+    Pro,
+    /// `history.openweathermap.org`, historical and statistical data
+    History,
+    /// `tile.openweathermap.org`, Weather Maps 1.0 tiles
+    Tile,
+    /// `maps.openweathermap.org`, Weather Maps 2.0 tiles
+    Maps,
+    /// `api.agromonitoring.com`, the Agro Monitoring API
+    Agro,
+}
+
+impl ApiHost {
+    /// The base URL used unless overridden.
+    pub fn default_base_url(self) -> &'static str {
+        match self {
+            ApiHost::Api => "https://api.openweathermap.org",
+            ApiHost::Pro => "https://pro.openweathermap.org",
+            ApiHost::History => "https://history.openweathermap.org",
+            ApiHost::Tile => "https://tile.openweathermap.org",
+            ApiHost::Maps => "https://maps.openweathermap.org",
+            ApiHost::Agro => "https://api.agromonitoring.com",
+        }
+    }
+}
+
+impl fmt::Display for ApiHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ApiHost::Api => "api",
+            ApiHost::Pro => "pro",
+            ApiHost::History => "history",
+            ApiHost::Tile => "tile",
+            ApiHost::Maps => "maps",
+            ApiHost::Agro => "agro",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// An endpoint of the API.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Endpoint {
+    /// Short name, used in logs, spans and observer events.
+    pub(crate) name: &'static str,
+    /// The host serving the endpoint.
+    pub(crate) host: ApiHost,
+    /// Path of the endpoint on its host.
+    pub(crate) path: &'static str,
+}
+
+impl Endpoint {
+    pub(crate) const fn new(name: &'static str, host: ApiHost, path: &'static str) -> Self {
+        Endpoint { name, host, path }
+    }
+}
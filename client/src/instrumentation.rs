@@ -0,0 +1,50 @@
+use std::{future::Future, time::Duration};
+
+use http::StatusCode;
+
+/// A `tracing` span around one API call, carrying the masked URL, the query's display name, the attempt number and,
+/// once known, the response status and latency.  Without the `tracing` feature every method is a no-op.
+///
+/// Callers must only ever hand this a URL that has already had its API key masked.
+pub(crate) struct CallSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl CallSpan {
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn new(endpoint: &'static str, masked_url: &str, query: &str, attempt: u32) -> CallSpan {
+        CallSpan {
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                "owm_api_call",
+                endpoint,
+                url = masked_url,
+                query,
+                attempt,
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            ),
+        }
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables, clippy::unused_self))]
+    pub(crate) fn record_status(&self, status: StatusCode) {
+        #[cfg(feature = "tracing")]
+        self.span.record("status", status.as_u16());
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables, clippy::unused_self))]
+    pub(crate) fn record_latency(&self, latency: Duration) {
+        #[cfg(feature = "tracing")]
+        #[allow(clippy::cast_possible_truncation)] // a call would have to last 584 million years to truncate
+        self.span.record("latency_ms", latency.as_millis() as u64);
+    }
+
+    /// Runs `future` inside the span.
+    pub(crate) async fn instrument<F: Future>(&self, future: F) -> F::Output {
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::instrument(future, self.span.clone());
+        future.await
+    }
+}
@@ -21,12 +21,49 @@
 #![doc = include_str!("../examples/get_multiple_readings.rs")]
 //! ```
 
+pub mod agro;
+mod api_key;
+mod builder;
+mod cache;
+pub mod cassette;
+#[cfg(feature = "citylist")]
+pub mod citylist;
 mod client;
 pub mod error;
+pub mod forecast;
+pub mod geo;
+pub mod history;
+mod hosts;
+mod instrumentation;
+mod iso;
 pub mod models;
+mod observer;
 mod options;
+pub mod provider;
 mod query;
+pub mod region;
+mod retry;
+#[cfg(feature = "tower")]
+pub mod service;
+pub mod solar;
+pub mod stations;
+pub mod statistics;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+pub mod summary;
+pub mod tiles;
+pub mod transport;
+pub mod uv_index;
+pub mod watch;
+mod weather_builder;
 
-pub use client::Client;
+pub use api_key::ApiKeySource;
+pub use builder::ClientBuilder;
+pub use cache::CachePolicy;
+pub use client::{Client, RawResponse};
+pub use hosts::ApiHost;
+pub use observer::{ClientObserver, RequestInfo};
 pub use options::ClientOptions;
-pub use query::Query;
+pub use query::{AnyQuery, Query};
+pub use retry::RetryPolicy;
+pub use transport::Transport;
@@ -1,6 +1,8 @@
-use std::fmt::Display;
+use std::{collections::BTreeMap, fmt::Display};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+pub use crate::weather_builder::CurrentWeatherBuilder;
 
 /// According to [OpenWeatherMap API Docs](https://openweathermap.org/weather-data) and experiments, the return
 /// types for each unit are:
@@ -36,15 +38,16 @@ impl Display for UnitSystem {
 }
 
 /// Used to query the weather in a particular city via a geocoding lookup
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct City {
-    /// in many countries format with {cityname},{subdivision} where "subdivision" is a state or province and is specified by the last part of a [ISO 3166-2 subdivision code](https://en.wikipedia.org/wiki/ISO_3166-2)
+    /// in many countries format with {cityname},{subdivision} where "subdivision" is a state or province and is specified by the last part of a [ISO 3166-2 subdivision code](https://en.wikipedia.org/wiki/ISO_3166-2).  [`Query::validate`](crate::Query::validate) only checks the subdivisions of the US, Canada and Australia
     pub name: String,
 
     /// An [ISO 3166-1](https://en.wikipedia.org/wiki/ISO_3166-1) 2-character country code
     pub country_code: String,
 
     /// When available will be rendered by [Display] instead of the `name`, `country_code`.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub display_name: Option<String>,
 }
 impl City {
@@ -68,11 +71,12 @@ impl Display for City {
 }
 
 /// Used to query the weather in a particular city using openweathermap's city id
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CityId {
     /// The [OpenWeatherMap city id](https://openweathermap.org/current#cityid).
     pub id: u32,
     /// When available will be rendered by [Display] instead of the city [CityId#id].
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub display_name: Option<String>,
 }
 impl CityId {
@@ -91,13 +95,14 @@ impl Display for CityId {
 }
 
 /// Used to query weather at a latitude and logitude.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Coord {
     /// City geo location, latitude
     pub lat: f64,
     /// City geo location, longitude
     pub lon: f64,
     /// When available will be rendered by [Display] instead of the lat and lon.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub display_name: Option<String>,
 }
 impl Coord {
@@ -119,17 +124,78 @@ impl Display for Coord {
     }
 }
 
+/// Used to query the weather in a postal code area.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Zip {
+    /// The postal code
+    pub zip_code: String,
+
+    /// An [ISO 3166-1](https://en.wikipedia.org/wiki/ISO_3166-1) 2-character country code
+    pub country_code: String,
+
+    /// When available will be rendered by [Display] instead of the `zip_code`, `country_code`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+}
+impl Zip {
+    /// Create an instance with just `zip_code` and `country_code`
+    pub fn new(zip_code: &str, country_code: &str) -> Zip {
+        Zip {
+            zip_code: zip_code.to_string(),
+            country_code: country_code.to_string(),
+            display_name: None,
+        }
+    }
+}
+impl Display for Zip {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.display_name {
+            Some(display_name) => write!(f, "{display_name}"),
+            None => write!(f, "{}, {}", self.zip_code, self.country_code),
+        }
+    }
+}
+
+/// A latitude/longitude aligned area.  When `west` is greater than `east` the box crosses the antimeridian.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    /// Southern edge, latitude
+    pub south: f64,
+    /// Western edge, longitude
+    pub west: f64,
+    /// Northern edge, latitude
+    pub north: f64,
+    /// Eastern edge, longitude
+    pub east: f64,
+}
+impl BoundingBox {
+    /// Create an instance from its edges
+    pub fn new(south: f64, west: f64, north: f64, east: f64) -> BoundingBox {
+        BoundingBox {
+            south,
+            west,
+            north,
+            east,
+        }
+    }
+}
+
+/// Fields of a response that the models don't know about, e.g. added to the API after this crate was released.
+pub type Extra = BTreeMap<String, serde_json::Value>;
+
 /// Main structure for responses from the free `OpenWeatherMap` API
 ///
-/// See their API response documenation [here](https://openweathermap.org/current#fields_json).
-#[derive(Debug, Deserialize)]
+/// See their API response documenation [here](https://openweathermap.org/current#fields_json).  Fields the API
+/// leaves out at some locations (polar regions, the open ocean) are optional.
+#[derive(Debug, Clone, Deserialize)]
 pub struct CurrentWeather {
     /// City geo location, longitude
     pub coord: Coord,
     /// Seems to generally be a collection of one
+    #[serde(default)]
     pub weather: Vec<Weather>,
     /// `OpenWeatherMap` documents as "Internal parameter"
-    pub base: String,
+    pub base: Option<String>,
 
     /// Main readings that are usually present in responses.  See [Main].
     pub main: Main,
@@ -139,7 +205,7 @@ pub struct CurrentWeather {
     /// See [Wind]
     pub wind: Wind,
     /// See [Clouds]
-    pub clouds: Clouds,
+    pub clouds: Option<Clouds>,
     /// Recent rain volume
     pub rain: Option<PrecipVolume>,
     /// Recent snow volume
@@ -147,19 +213,32 @@ pub struct CurrentWeather {
     /// Time of data calculation, unix, UTC (in seconds)
     pub dt: i64,
     /// See [Sys]
+    #[serde(default)]
     pub sys: Sys,
     /// Shift in seconds from UTC
+    #[serde(default)]
     pub timezone: i64,
-    /// City ID
+    /// City ID, 0 away from any city
+    #[serde(default)]
     pub id: u64,
-    /// City name
+    /// City name, empty away from any city
+    #[serde(default)]
     pub name: String,
     /// Internal parameter
-    pub cod: u64,
+    pub cod: Option<u64>,
+    /// See [Extra]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+impl CurrentWeather {
+    /// Builds a reading from defaults, for tests.  See [`CurrentWeatherBuilder`].
+    pub fn builder() -> CurrentWeatherBuilder {
+        CurrentWeatherBuilder::default()
+    }
 }
 
 /// Weather condition description
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Weather {
     /// Weather condition id
     pub id: u64,
@@ -169,10 +248,31 @@ pub struct Weather {
     pub description: String,
     /// Weather icon id
     pub icon: String,
+    /// See [Extra]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+impl Weather {
+    /// Create an instance from a [condition](https://openweathermap.org/weather-conditions)
+    pub fn new(id: u64, main: &str, description: &str, icon: &str) -> Weather {
+        Weather {
+            id,
+            main: main.to_string(),
+            description: description.to_string(),
+            icon: icon.to_string(),
+            extra: Extra::new(),
+        }
+    }
+}
+impl Default for Weather {
+    /// Clear sky, during the day.
+    fn default() -> Self {
+        Weather::new(800, "Clear", "clear sky", "01d")
+    }
 }
 
 /// Detailed weather report
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Main {
     /// Temperature. Unit Default: Kelvin, Metric: Celsius, Imperial: Fahrenheit.
     pub temp: f64,
@@ -190,29 +290,54 @@ pub struct Main {
     pub grnd_level: Option<f64>,
     /// Humidity, %
     pub humidity: f64,
+    /// See [Extra]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+impl Default for Main {
+    /// A mild 15°C at standard pressure and 60% humidity.
+    fn default() -> Self {
+        Main {
+            temp: 15.0,
+            feels_like: 15.0,
+            temp_min: 15.0,
+            temp_max: 15.0,
+            pressure: 1013.0,
+            sea_level: None,
+            grnd_level: None,
+            humidity: 60.0,
+            extra: Extra::new(),
+        }
+    }
 }
 
 /// Detailed wind report
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct Wind {
     /// Wind speed. Unit Default: meter/sec, Metric: meter/sec, Imperial: miles/hour.
     pub speed: f64,
-    /// Wind direction, degrees (meteorological)
-    pub deg: f64,
+    /// Wind direction, degrees (meteorological).  Missing when the wind is calm or variable.
+    pub deg: Option<f64>,
     /// Wind gust. Unit Default: meter/sec, Metric: meter/sec, Imperial: miles/hour
     pub gust: Option<f64>,
+    /// See [Extra]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 /// Cloudiness readings
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct Clouds {
     /// Cloudiness, %
     #[serde(rename(deserialize = "all"))]
     pub cloudiness: f64,
+    /// See [Extra]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 /// 1- and 3- hour precipitation amounts.  Used for both rain and snow.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct PrecipVolume {
     /// Volume for the last 1 hour, mm
     #[serde(rename(deserialize = "1h"))]
@@ -221,12 +346,16 @@ pub struct PrecipVolume {
     /// Volume for the last 3 hours, mm
     #[serde(rename(deserialize = "3h"))]
     pub three_hour: Option<f64>,
+    /// See [Extra]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 /// Additional information
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct Sys {
-    /// Internal parameter    
+    /// Internal parameter
+    #[serde(rename(deserialize = "type"))]
     pub type_: Option<u64>,
     /// Internal parameter
     pub id: Option<u64>,
@@ -234,8 +363,15 @@ pub struct Sys {
     pub message: Option<f64>,
     /// Country code (GB, JP etc.)
     pub country: Option<String>,
-    /// Sunrise time, unix, UTC
-    pub sunrise: i64,
-    /// Sunset time, unix, UTC
-    pub sunset: i64,
+    /// Sunrise time, unix, UTC.  Missing during polar day and night.
+    pub sunrise: Option<i64>,
+    /// Sunset time, unix, UTC.  Missing during polar day and night.
+    pub sunset: Option<i64>,
+    /// See [Extra]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
+
+/// A reading of light rain in Paris, in imperial units, shared by the tests.
+#[cfg(test)]
+pub(crate) const PARIS: &str = include_str!("../tests/fixtures/current_weather/paris.json");
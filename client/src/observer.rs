@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use http::StatusCode;
+
+use crate::error::ApiCallError;
+
+/// Describes the API call an observer callback is about.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestInfo<'a> {
+    /// Short name of the API endpoint, e.g. `"weather"`.
+    pub endpoint: &'static str,
+    /// The request URL with the API key masked.
+    pub url: &'a str,
+    /// The [Display](std::fmt::Display) form of the query, which is its `display_name` when it has one.
+    pub query: &'a str,
+    /// 1 for the first attempt, incremented for each retry.
+    pub attempt: u32,
+}
+
+/// Receives notifications about the lifecycle of the API calls made by a [`Client`](crate::Client).
+///
+/// Register an implementation with [`Client::add_observer`](crate::Client::add_observer).  Every method has an
+/// empty default implementation, so implement only the events of interest.  Callbacks run inline on the calling
+/// task and should return quickly.  URLs handed to observers always have their API key masked.
+pub trait ClientObserver: Send + Sync {
+    /// Called just before a request is sent.
+    fn on_request_start(&self, request: &RequestInfo) {
+        let _ = request;
+    }
+
+    /// Called when the whole response has been read.  `latency` runs from handing the request to the
+    /// [`Transport`](crate::transport::Transport) until then, so it includes any time the request waits in the
+    /// middleware of a custom transport, e.g. a rate limit layered under the client.  Layer such limits above the
+    /// client to keep their wait out of it.
+    fn on_response(&self, request: &RequestInfo, status: StatusCode, latency: Duration) {
+        let _ = (request, status, latency);
+    }
+
+    /// Called when no response arrives, e.g. the connection fails or times out.  `latency` is measured like in
+    /// [`on_response`](ClientObserver::on_response), up to the failure.
+    fn on_error(&self, request: &RequestInfo, error: &ApiCallError, latency: Duration) {
+        let _ = (request, error, latency);
+    }
+
+    /// Called when a failed attempt is going to be retried after `delay`.
+    fn on_retry(&self, request: &RequestInfo, delay: Duration) {
+        let _ = (request, delay);
+    }
+
+    /// Called when a response is served from the client's cache instead of the API.
+    fn on_cache_hit(&self, request: &RequestInfo) {
+        let _ = request;
+    }
+
+    /// Called when a cacheable request is not in the client's cache and will be sent to the API.
+    fn on_cache_miss(&self, request: &RequestInfo) {
+        let _ = request;
+    }
+
+    /// Called when a `429 Too Many Requests` response is going to be retried after the `wait` its `Retry-After`
+    /// header asked for.  Rate limits in middleware are not seen by the client and aren't reported.
+    fn on_retry_after(&self, request: &RequestInfo, wait: Duration) {
+        let _ = (request, wait);
+    }
+
+    /// Called when a successful response body cannot be parsed into the expected model.
+    fn on_parse_failure(&self, request: &RequestInfo, error: &serde_yaml::Error) {
+        let _ = (request, error);
+    }
+}
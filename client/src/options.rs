@@ -1,7 +1,13 @@
 use serde::Deserialize;
-use std::fmt;
+use std::{collections::HashMap, fmt};
+use url::Url;
 
-use crate::error::InvalidOptionsError;
+use crate::{
+    api_key::{mask, mask_all, ApiKeySource},
+    cassette::{Cassette, CassetteMode},
+    error::InvalidOptionsError,
+    hosts::ApiHost,
+};
 
 use super::models::UnitSystem;
 
@@ -9,10 +15,15 @@ use super::models::UnitSystem;
 #[derive(Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ClientOptions {
-    /// An api key required to call the API.
+    /// An api key required to call the API.  May be left blank when replaying a [`Cassette`].
     #[serde(default = "String::new")]
     pub api_key: String,
 
+    /// An alternative to `api_key` for keys held in a named environment variable, a (re-readable) file or a pool
+    /// of several keys.  Takes precedence over `api_key` when present.  See [`ApiKeySource`].
+    #[serde(default)]
+    pub api_key_source: Option<ApiKeySource>,
+
     /// See <https://openweathermap.org/current#multi> for currently supported languages.  This library will not attempt to validate the language passed in.
     #[serde(default = "ClientOptions::default_language")]
     pub language: String,
@@ -20,6 +31,20 @@ pub struct ClientOptions {
     /// Controls the units of certain metrics returned from the API.
     #[serde(default = "ClientOptions::default_units")]
     pub units: UnitSystem,
+
+    /// Base URLs to use instead of the defaults for some of the [`ApiHost`]s, e.g. to route calls through a proxy or to
+    /// a local stand-in server.
+    #[serde(default)]
+    pub hosts: HashMap<ApiHost, String>,
+
+    /// Record API calls to, or replay them from, a cassette file.  See [`Cassette`].
+    #[serde(default)]
+    pub cassette: Option<Cassette>,
+
+    /// Also allow plain `http` URLs in `hosts`, e.g. for a local stand-in server in tests.  The api key is then sent
+    /// unencrypted.  Off by default: only https is used.
+    #[serde(default)]
+    pub allow_insecure_http: bool,
 }
 
 impl ClientOptions {
@@ -38,17 +63,53 @@ impl ClientOptions {
         UnitSystem::Metric
     }
 
-    /// Returns the API key with most of the characters masked out.
+    /// The [`ApiKeySource`] in effect: `api_key_source` if present, otherwise the inline `api_key`.
+    pub fn effective_api_key_source(&self) -> ApiKeySource {
+        match &self.api_key_source {
+            Some(source) => source.clone(),
+            None => ApiKeySource::Inline(self.api_key.clone()),
+        }
+    }
+
+    /// The base URL of `host`: its entry in `hosts` if present, otherwise [`ApiHost::default_base_url`].
+    pub fn base_url(&self, host: ApiHost) -> &str {
+        self.hosts.get(&host).map_or(host.default_base_url(), String::as_str)
+    }
+
+    /// Returns the API key with most of the characters masked out.  When several keys are in effect, they are all
+    /// masked and joined with `", "`.  An `env` or `file` [`ApiKeySource`] is read on each call, and contributes
+    /// nothing if it can't be.
     pub fn masked_api_key(&self) -> String {
-        mask(&self.api_key)
+        self.api_keys()
+            .iter()
+            .map(|key| mask(key))
+            .collect::<Vec<String>>()
+            .join(", ")
     }
 
-    /// Ensures an `api_key` is provided
+    /// Ensures an `api_key` (unless replaying a cassette) or a valid `api_key_source` is provided and that the `hosts` are https URLs, or http
+    /// ones too when `allow_insecure_http` is set.
     ///
     /// # Errors
     /// Errors that cannot be validated during input parsing.
     pub fn validate(&self) -> Result<(), InvalidOptionsError> {
-        if self.api_key.is_empty() {
+        for (host, base_url) in &self.hosts {
+            match Url::parse(base_url) {
+                Ok(url) if url.scheme() == "https" => {}
+                Ok(url) if url.scheme() == "http" && self.allow_insecure_http => {}
+                _ => {
+                    return Err(InvalidOptionsError {
+                        message: format!("hosts.{host} must be an https URL, got {base_url:?}"),
+                    })
+                }
+            }
+        }
+
+        if let Some(source) = &self.api_key_source {
+            return source.validate();
+        }
+
+        if self.api_key.is_empty() && !self.is_replaying() {
             return Err(InvalidOptionsError {
                 message: "api_key must be non-blank".to_string(),
             });
@@ -57,9 +118,25 @@ impl ClientOptions {
         Ok(())
     }
 
-    /// Take an arbitrary string that might have the `self.api_key` in it and returns a string with that all occurrences of the key masked.
+    /// Take an arbitrary string that might have the `self.api_key` (or any key of the `api_key_source`) in it and returns a string with that all occurrences of the key(s) masked.
+    /// Like [`masked_api_key`](ClientOptions::masked_api_key), it reads an `env` or `file` source on each call.
     pub fn mask_api_key_if_present(&self, any_string: &str) -> String {
-        any_string.replace(&self.api_key, &self.masked_api_key())
+        mask_all(&self.api_keys(), any_string)
+    }
+
+    /// Whether responses are served from a [`Cassette`] rather than the API.
+    pub(crate) fn is_replaying(&self) -> bool {
+        self.cassette
+            .as_ref()
+            .is_some_and(|cassette| cassette.mode == CassetteMode::Replay)
+    }
+
+    fn api_keys(&self) -> Vec<String> {
+        let mut keys = self.effective_api_key_source().current_keys();
+        if !self.api_key.is_empty() && !keys.contains(&self.api_key) {
+            keys.push(self.api_key.clone());
+        }
+        keys
     }
 }
 
@@ -68,29 +145,28 @@ impl Default for ClientOptions {
     fn default() -> Self {
         Self {
             api_key: Self::default_api_key(),
+            api_key_source: None,
             language: Self::default_language(),
             units: Self::default_units(),
+            hosts: HashMap::new(),
+            cassette: None,
+            allow_insecure_http: false,
         }
     }
 }
 
-fn mask(s: &str) -> String {
-    let mut masked: String = s.to_string();
-    if !s.is_empty() {
-        let range = (masked.len() - 1).clamp(0, 3)..;
-        masked.replace_range(range, "****");
-    }
-    masked
-}
-
 impl fmt::Debug for ClientOptions {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Config {{ api_key: \"{}\", language: \"{}\", units: {} }}",
+            "Config {{ api_key: \"{}\", api_key_source: {:?}, language: \"{}\", units: {}, hosts: {:?}, cassette: {:?}, allow_insecure_http: {} }}",
             mask(&self.api_key),
+            self.api_key_source,
             self.language,
-            self.units
+            self.units,
+            self.hosts,
+            self.cassette,
+            self.allow_insecure_http
         )
     }
 }
@@ -178,4 +254,124 @@ units: imperial
             "I think th**** quote is, \"It was th**** best of times, it was th**** worst of times, ...\""
         );
     }
+
+    #[test]
+    fn api_key_may_only_be_blank_when_replaying() {
+        let mut options = ClientOptions {
+            api_key: String::new(),
+            cassette: Some(Cassette::record("calls.yaml")),
+            ..ClientOptions::default()
+        };
+        assert!(options.validate().is_err());
+
+        options.cassette = Some(Cassette::replay("calls.yaml"));
+        assert!(options.validate().is_ok());
+    }
+
+    #[test]
+    fn serde_parse_api_key_source() {
+        let parsed: ClientOptions = serde_yaml::from_str(
+            "\
+api_key_source:
+  pool: [ first_key, second_key ]
+",
+        )
+        .unwrap();
+
+        assert!(parsed.validate().is_ok());
+        assert_eq!(
+            parsed.effective_api_key_source(),
+            ApiKeySource::Pool(vec!["first_key".to_string(), "second_key".to_string()])
+        );
+    }
+
+    #[test]
+    fn api_key_source_takes_precedence_over_api_key() {
+        let options = ClientOptions {
+            api_key: "inline_key".to_string(),
+            api_key_source: Some(ApiKeySource::Env("SOME_VAR".to_string())),
+            ..ClientOptions::default()
+        };
+        assert_eq!(
+            options.effective_api_key_source(),
+            ApiKeySource::Env("SOME_VAR".to_string())
+        );
+    }
+
+    #[test]
+    fn client_options_masks_every_pooled_key() {
+        let options = ClientOptions {
+            api_key: String::new(),
+            api_key_source: Some(ApiKeySource::Pool(vec![
+                "PLAINTEXT_ONE".to_string(),
+                "SECRET_TWO".to_string(),
+            ])),
+            ..ClientOptions::default()
+        };
+        assert_eq!(options.masked_api_key(), "PLA****, SEC****");
+        assert_eq!(
+            options.mask_api_key_if_present("appid=PLAINTEXT_ONE appid=SECRET_TWO"),
+            "appid=PLA**** appid=SEC****"
+        );
+
+        let debug = format!("{options:?}");
+        assert!(!debug.contains("PLAINTEXT"));
+        assert!(!debug.contains("SECRET"));
+    }
+
+    #[test]
+    fn serde_parse_hosts() {
+        let parsed: ClientOptions = serde_yaml::from_str(
+            "\
+api_key: abc123
+hosts:
+  pro: https://owm-proxy.example.com/pro
+",
+        )
+        .unwrap();
+
+        assert!(parsed.validate().is_ok());
+        assert_eq!(parsed.base_url(ApiHost::Pro), "https://owm-proxy.example.com/pro");
+        assert_eq!(parsed.base_url(ApiHost::Api), "https://api.openweathermap.org");
+    }
+
+    #[test]
+    fn hosts_must_be_https_urls() {
+        let mut options = ClientOptions {
+            api_key: "abc123".to_string(),
+            ..ClientOptions::default()
+        };
+        options
+            .hosts
+            .insert(ApiHost::History, "history.example.com".to_string());
+        assert!(options.validate().is_err());
+
+        options
+            .hosts
+            .insert(ApiHost::History, "ftp://history.example.com".to_string());
+        assert!(options.validate().is_err());
+
+        options
+            .hosts
+            .insert(ApiHost::History, "http://127.0.0.1:8080".to_string());
+        assert!(options.validate().is_err());
+    }
+
+    #[test]
+    fn http_hosts_must_be_allowed_explicitly() {
+        let mut options = ClientOptions {
+            api_key: "abc123".to_string(),
+            allow_insecure_http: true,
+            ..ClientOptions::default()
+        };
+        options
+            .hosts
+            .insert(ApiHost::History, "http://127.0.0.1:8080".to_string());
+        assert!(options.validate().is_ok());
+
+        options
+            .hosts
+            .insert(ApiHost::History, "ftp://history.example.com".to_string());
+        assert!(options.validate().is_err());
+    }
 }
@@ -0,0 +1,309 @@
+//! A seam for sources of current weather other than `OpenWeatherMap`.  A [`WeatherProvider`] answers with a
+//! provider-neutral [`Observation`]; the [`Client`] is one, [`FailoverProvider`] falls back from one provider to the
+//! next and [`MockProvider`] stands in for a provider in tests.
+
+use log::warn;
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::{
+    error::ProviderError,
+    models::{CurrentWeather, UnitSystem},
+    AnyQuery, Client,
+};
+
+/// The future returned by [`WeatherProvider::current`].
+pub type ProviderFuture<'a> = Pin<Box<dyn Future<Output = Result<Observation, ProviderError>> + Send + 'a>>;
+
+/// A source of current weather.
+pub trait WeatherProvider: Send + Sync {
+    /// Identifies the provider in observations, logs and errors.
+    fn name(&self) -> &str;
+
+    /// The current weather at `location`.  A provider that can't look up some kinds of location (e.g. zip codes)
+    /// fails with [`ProviderError::UnsupportedLocation`].
+    fn current<'a>(&'a self, location: &'a AnyQuery) -> ProviderFuture<'a>;
+}
+
+/// The current weather at a location, in metric units whatever the provider's own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    /// [`WeatherProvider::name`] of the provider that made the observation
+    pub provider: String,
+    /// Name of the location, as known to the provider
+    pub location: String,
+    /// Latitude of the location
+    pub lat: f64,
+    /// Longitude of the location
+    pub lon: f64,
+    /// Time of the observation, unix, UTC (in seconds)
+    pub observed_at: i64,
+    /// Short description of the conditions, e.g. "light rain"
+    pub conditions: String,
+    /// Temperature, °C
+    pub temperature: f64,
+    /// Temperature accounting for the human perception of weather, °C
+    pub feels_like: Option<f64>,
+    /// Relative humidity, %
+    pub humidity: f64,
+    /// Atmospheric pressure at sea level, hPa
+    pub pressure: f64,
+    /// Wind speed, meter/sec
+    pub wind_speed: f64,
+    /// Wind direction, degrees (meteorological)
+    pub wind_direction: Option<f64>,
+    /// Cloud cover, %
+    pub cloud_cover: Option<f64>,
+    /// Rain and snow over the last hour, mm
+    pub precipitation_last_hour: Option<f64>,
+    /// Visibility, meter
+    pub visibility: Option<f64>,
+}
+
+impl Observation {
+    /// Converts an `OpenWeatherMap` reading made in `units`.
+    pub fn from_current_weather(reading: &CurrentWeather, units: UnitSystem) -> Self {
+        let temperature = |t: f64| match units {
+            UnitSystem::Metric => t,
+            UnitSystem::Standard => t - 273.15,
+            UnitSystem::Imperial => (t - 32.0) * 5.0 / 9.0,
+        };
+        let speed = |s: f64| match units {
+            UnitSystem::Metric | UnitSystem::Standard => s,
+            UnitSystem::Imperial => s * 0.447_04,
+        };
+        let last_hour = [&reading.rain, &reading.snow]
+            .into_iter()
+            .filter_map(|volume| volume.as_ref().and_then(|v| v.one_hour))
+            .reduce(|a, b| a + b);
+
+        Observation {
+            provider: OPENWEATHERMAP.to_string(),
+            location: reading.name.clone(),
+            lat: reading.coord.lat,
+            lon: reading.coord.lon,
+            observed_at: reading.dt,
+            conditions: reading
+                .weather
+                .iter()
+                .map(|w| w.description.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            temperature: temperature(reading.main.temp),
+            feels_like: Some(temperature(reading.main.feels_like)),
+            humidity: reading.main.humidity,
+            pressure: reading.main.sea_level.unwrap_or(reading.main.pressure),
+            wind_speed: speed(reading.wind.speed),
+            wind_direction: reading.wind.deg,
+            cloud_cover: reading.clouds.as_ref().map(|clouds| clouds.cloudiness),
+            precipitation_last_hour: last_hour,
+            visibility: reading.visibility.map(f64::from),
+        }
+    }
+}
+
+const OPENWEATHERMAP: &str = "openweathermap";
+
+impl WeatherProvider for Client {
+    fn name(&self) -> &str {
+        OPENWEATHERMAP
+    }
+
+    fn current<'a>(&'a self, location: &'a AnyQuery) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let reading = self.fetch_weather(location).await?;
+            Ok(Observation::from_current_weather(&reading, self.units()))
+        })
+    }
+}
+
+/// Asks each of its providers in turn until one has an observation.
+pub struct FailoverProvider {
+    providers: Vec<Arc<dyn WeatherProvider>>,
+}
+
+impl FailoverProvider {
+    /// Tries `providers` in the order given.
+    pub fn new(providers: Vec<Arc<dyn WeatherProvider>>) -> Self {
+        FailoverProvider { providers }
+    }
+}
+
+impl WeatherProvider for FailoverProvider {
+    fn name(&self) -> &'static str {
+        "failover"
+    }
+
+    /// The first observation of a provider.  [`ProviderError::AllFailed`] with every provider's error if none has
+    /// one.
+    fn current<'a>(&'a self, location: &'a AnyQuery) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let mut failures = Vec::new();
+            for provider in &self.providers {
+                match provider.current(location).await {
+                    Ok(observation) => return Ok(observation),
+                    Err(e) => {
+                        warn!(
+                            "{} has no observation of {location}, failing over. Error: {e}",
+                            provider.name()
+                        );
+                        failures.push((provider.name().to_string(), e));
+                    }
+                }
+            }
+            Err(ProviderError::AllFailed(failures))
+        })
+    }
+}
+
+/// A [`WeatherProvider`] for tests, answering with canned observations and remembering what it was asked for.
+///
+/// ```
+/// # async fn example() {
+/// use openweathermap_client::{models::City, provider::{MockProvider, WeatherProvider}, AnyQuery};
+///
+/// let provider = MockProvider::failing("primary", "outage");
+/// let location = AnyQuery::City(City::new("Paris", "FR"));
+/// assert!(provider.current(&location).await.is_err());
+/// assert_eq!(provider.calls(), ["Paris, FR"]);
+/// # }
+/// ```
+pub struct MockProvider {
+    name: String,
+    default: Result<Observation, String>,
+    queued: Mutex<VecDeque<Result<Observation, ProviderError>>>,
+    calls: Mutex<Vec<String>>,
+}
+
+impl MockProvider {
+    /// Always observes `observation`, relabelled with this provider's `name`.
+    pub fn returning(name: impl Into<String>, observation: Observation) -> Self {
+        MockProvider::with_default(name.into(), Ok(observation))
+    }
+
+    /// Always fails with [`ProviderError::Unavailable`] and `message`.
+    pub fn failing(name: impl Into<String>, message: impl Into<String>) -> Self {
+        MockProvider::with_default(name.into(), Err(message.into()))
+    }
+
+    fn with_default(name: String, default: Result<Observation, String>) -> Self {
+        MockProvider {
+            name,
+            default,
+            queued: Mutex::new(VecDeque::new()),
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Answers the next call with `result` instead of the default.  Queued results are used in order.
+    pub fn push_result(&self, result: Result<Observation, ProviderError>) {
+        self.queued
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push_back(result);
+    }
+
+    /// The locations asked for so far, in their [Display](std::fmt::Display) form.
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+impl WeatherProvider for MockProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn current<'a>(&'a self, location: &'a AnyQuery) -> ProviderFuture<'a> {
+        self.calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(location.to_string());
+        let queued = self.queued.lock().unwrap_or_else(PoisonError::into_inner).pop_front();
+        let result = queued.unwrap_or_else(|| match &self.default {
+            Ok(observation) => Ok(Observation {
+                provider: self.name.clone(),
+                ..observation.clone()
+            }),
+            Err(message) => Err(ProviderError::Unavailable {
+                provider: self.name.clone(),
+                message: message.clone(),
+            }),
+        });
+        Box::pin(async move { result })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{City, PARIS};
+
+    fn paris() -> Observation {
+        let reading: CurrentWeather = serde_yaml::from_str(PARIS).unwrap();
+        Observation::from_current_weather(&reading, UnitSystem::Imperial)
+    }
+
+    fn location() -> AnyQuery {
+        AnyQuery::City(City::new("Paris", "FR"))
+    }
+
+    #[test]
+    fn readings_are_converted_to_metric() {
+        let observation = paris();
+
+        assert_eq!(observation.provider, "openweathermap");
+        assert_eq!(observation.conditions, "light rain");
+        assert!((observation.temperature - 15.0).abs() < 1e-9);
+        assert!((observation.feels_like.unwrap() - 14.0).abs() < 1e-9);
+        assert!((observation.wind_speed - 4.4704).abs() < 1e-9);
+        assert!((observation.pressure - 1021.0).abs() < 1e-9);
+        assert_eq!(observation.precipitation_last_hour, Some(0.4));
+        assert_eq!(observation.visibility, Some(9000.0));
+    }
+
+    #[tokio::test]
+    async fn failover_uses_the_first_provider_with_an_observation() {
+        let primary = Arc::new(MockProvider::failing("primary", "outage"));
+        let secondary = Arc::new(MockProvider::returning("secondary", paris()));
+        let failover = FailoverProvider::new(vec![primary.clone(), secondary.clone()]);
+
+        let observation = failover.current(&location()).await.unwrap();
+
+        assert_eq!(observation.provider, "secondary");
+        assert_eq!(primary.calls(), ["Paris, FR"]);
+        assert_eq!(secondary.calls(), ["Paris, FR"]);
+    }
+
+    #[tokio::test]
+    async fn failover_reports_every_failure() {
+        let failover = FailoverProvider::new(vec![
+            Arc::new(MockProvider::failing("primary", "outage")),
+            Arc::new(MockProvider::failing("secondary", "maintenance")),
+        ]);
+
+        let error = failover.current(&location()).await.unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "every provider failed: primary: primary is unavailable: outage; \
+             secondary: secondary is unavailable: maintenance"
+        );
+    }
+
+    #[tokio::test]
+    async fn queued_results_come_before_the_default() {
+        let provider = MockProvider::returning("mock", paris());
+        provider.push_result(Err(ProviderError::Unavailable {
+            provider: "mock".to_string(),
+            message: "blip".to_string(),
+        }));
+
+        assert!(provider.current(&location()).await.is_err());
+        assert!(provider.current(&location()).await.is_ok());
+    }
+}
@@ -1,6 +1,13 @@
 use core::fmt;
+use std::str::FromStr;
 
-use super::models::{City, CityId, Coord};
+use serde::{Deserialize, Serialize};
+
+use super::models::{City, CityId, Coord, Zip};
+use crate::{
+    error::{QueryParseError, QueryValidationError},
+    iso,
+};
 
 pub type QueryParameter = (&'static str, String);
 pub type QueryParameters = Vec<QueryParameter>;
@@ -11,6 +18,19 @@ pub trait Query: fmt::Debug + fmt::Display + Send + Sync {
     fn get_display_name(&self) -> &Option<String>;
     /// Query parameters and values that must be added to API call URL.
     fn query_params(&self) -> QueryParameters;
+    /// Checks what can be checked without calling the API, so that mistakes don't show up later as a 400 or 404
+    /// response.  Accepts anything unless overridden.
+    ///
+    /// # Errors
+    /// The first problem found.
+    fn validate(&self) -> Result<(), QueryValidationError> {
+        Ok(())
+    }
+    /// The coordinates asked about, for queries of a point rather than of a named place.  The reading is then that
+    /// of the nearest station, see [`crate::geo`].
+    fn coord(&self) -> Option<&Coord> {
+        None
+    }
 }
 
 /// Queries weather at a geographic location using `lat={lat},lon={lon}` as described [here](https://openweathermap.org/current#geo).
@@ -22,6 +42,21 @@ impl Query for Coord {
     fn query_params(&self) -> QueryParameters {
         vec![("lat", self.lat.to_string()), ("lon", self.lon.to_string())]
     }
+
+    /// Latitude must be within ±90 and longitude within ±180.
+    fn validate(&self) -> Result<(), QueryValidationError> {
+        if !(-90.0..=90.0).contains(&self.lat) {
+            return Err(QueryValidationError::LatitudeOutOfRange(self.lat));
+        }
+        if !(-180.0..=180.0).contains(&self.lon) {
+            return Err(QueryValidationError::LongitudeOutOfRange(self.lon));
+        }
+        Ok(())
+    }
+
+    fn coord(&self) -> Option<&Coord> {
+        Some(self)
+    }
 }
 
 /// Queries weather at a city using `q={city},{country_code}` as described [here](https://openweathermap.org/current#name).
@@ -33,6 +68,29 @@ impl Query for City {
     fn query_params(&self) -> QueryParameters {
         vec![("q", format!("{},{}", self.name, self.country_code))]
     }
+
+    /// The name must be non-blank and the country code ISO 3166-1 alpha-2.  In the `{name},{subdivision}` form the
+    /// subdivision must be an ISO 3166-2 code of the country.  That is only checked for the US, Canada and Australia,
+    /// the subdivisions of other countries are accepted as they are.
+    fn validate(&self) -> Result<(), QueryValidationError> {
+        validate_country_code(&self.country_code)?;
+        let (name, subdivision) = match self.name.rsplit_once(',') {
+            Some((name, subdivision)) => (name, Some(subdivision.trim())),
+            None => (self.name.as_str(), None),
+        };
+        if name.trim().is_empty() {
+            return Err(QueryValidationError::BlankCityName);
+        }
+        if let (Some(subdivision), Some(known)) = (subdivision, iso::subdivisions(&self.country_code)) {
+            if known.binary_search(&subdivision.to_ascii_uppercase().as_str()).is_err() {
+                return Err(QueryValidationError::UnknownSubdivision {
+                    country_code: self.country_code.clone(),
+                    subdivision: subdivision.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Queries weather at a city by it's openweathermap id as described [here](https://openweathermap.org/current#cityid).
@@ -44,14 +102,210 @@ impl Query for CityId {
     fn query_params(&self) -> QueryParameters {
         vec![("id", self.id.to_string())]
     }
+
+    /// The id must be non-zero.
+    fn validate(&self) -> Result<(), QueryValidationError> {
+        if self.id == 0 {
+            return Err(QueryValidationError::ZeroCityId);
+        }
+        Ok(())
+    }
+}
+
+/// Queries weather in a postal code area using `zip={zip code},{country_code}` as described [here](https://openweathermap.org/current#zip).
+impl Query for Zip {
+    fn get_display_name(&self) -> &Option<String> {
+        &self.display_name
+    }
+
+    fn query_params(&self) -> QueryParameters {
+        vec![("zip", format!("{},{}", self.zip_code, self.country_code))]
+    }
+
+    /// The zip code must be non-blank and the country code ISO 3166-1 alpha-2.
+    fn validate(&self) -> Result<(), QueryValidationError> {
+        validate_country_code(&self.country_code)?;
+        if self.zip_code.trim().is_empty() {
+            return Err(QueryValidationError::BlankZipCode);
+        }
+        Ok(())
+    }
+}
+
+/// Any one of the supported query kinds, for when queries need to be stored together, read from one list in a
+/// config file or taken from a command line.
+///
+/// Serialized with a `type` tag alongside the query's own fields, e.g.
+///
+/// ```yaml
+/// - type: city
+///   name: Paris
+///   country_code: FR
+/// - type: coord
+///   lat: 48.85
+///   lon: 2.35
+/// ```
+///
+/// Also parsed by [`FromStr`] from shorthand strings:
+///
+/// | Shorthand               | Query                                 |
+/// | ----------------------- | ------------------------------------- |
+/// | `Paris,FR`              | [City] named `Paris` in country `FR`  |
+/// | `Springfield,IL,US`     | [City] named `Springfield,IL` in `US` |
+/// | `id:2988507`            | [`CityId`] `2988507`                  |
+/// | `48.85,2.35`            | [Coord] at lat `48.85`, lon `2.35`    |
+/// | `zip:94040,US`          | [Zip] `94040` in country `US`         |
+///
+/// A `city:` or `coord:` prefix may be used to be explicit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnyQuery {
+    /// See [City]
+    City(City),
+    /// See [`CityId`]
+    CityId(CityId),
+    /// See [Coord]
+    Coord(Coord),
+    /// See [Zip]
+    Zip(Zip),
+}
+
+impl AnyQuery {
+    /// The wrapped query.
+    pub fn as_query(&self) -> &dyn Query {
+        match self {
+            AnyQuery::City(q) => q,
+            AnyQuery::CityId(q) => q,
+            AnyQuery::Coord(q) => q,
+            AnyQuery::Zip(q) => q,
+        }
+    }
+}
+
+impl Query for AnyQuery {
+    fn get_display_name(&self) -> &Option<String> {
+        self.as_query().get_display_name()
+    }
+
+    fn query_params(&self) -> QueryParameters {
+        self.as_query().query_params()
+    }
+
+    fn validate(&self) -> Result<(), QueryValidationError> {
+        self.as_query().validate()
+    }
+
+    fn coord(&self) -> Option<&Coord> {
+        self.as_query().coord()
+    }
+}
+
+impl fmt::Display for AnyQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_query(), f)
+    }
+}
+
+impl From<City> for AnyQuery {
+    fn from(query: City) -> Self {
+        AnyQuery::City(query)
+    }
+}
+
+impl From<CityId> for AnyQuery {
+    fn from(query: CityId) -> Self {
+        AnyQuery::CityId(query)
+    }
+}
+
+impl From<Coord> for AnyQuery {
+    fn from(query: Coord) -> Self {
+        AnyQuery::Coord(query)
+    }
+}
+
+impl From<Zip> for AnyQuery {
+    fn from(query: Zip) -> Self {
+        AnyQuery::Zip(query)
+    }
+}
+
+impl FromStr for AnyQuery {
+    type Err = QueryParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input = s.trim();
+        let error = |message: &str| QueryParseError {
+            input: s.to_string(),
+            message: message.to_string(),
+        };
+
+        if let Some(id) = input.strip_prefix("id:") {
+            return match id.trim().parse::<u32>() {
+                Ok(id) => Ok(AnyQuery::CityId(CityId::new(id))),
+                Err(_) => Err(error("expected a numeric city id after `id:`")),
+            };
+        }
+
+        if let Some(zip) = input.strip_prefix("zip:") {
+            return match split_country_code(zip) {
+                Some((zip_code, country_code)) => Ok(AnyQuery::Zip(Zip::new(zip_code, country_code))),
+                None => Err(error("expected `zip:{zip code},{country code}`")),
+            };
+        }
+
+        if let Some(coord) = input.strip_prefix("coord:") {
+            return parse_coord(coord).ok_or_else(|| error("expected `coord:{lat},{lon}`"));
+        }
+
+        if let Some(city) = input.strip_prefix("city:") {
+            return parse_city(city).ok_or_else(|| error("expected `city:{name},{country code}`"));
+        }
+
+        parse_coord(input).or_else(|| parse_city(input)).ok_or_else(|| {
+            error("expected `{name},{country code}`, `{lat},{lon}`, `id:{city id}` or `zip:{zip code},{country code}`")
+        })
+    }
+}
+
+fn validate_country_code(country_code: &str) -> Result<(), QueryValidationError> {
+    if iso::is_country_code(country_code.trim()) {
+        Ok(())
+    } else {
+        Err(QueryValidationError::UnknownCountryCode(country_code.to_string()))
+    }
+}
+
+/// Splits `"{value},{country code}"` at its last comma.
+fn split_country_code(s: &str) -> Option<(&str, &str)> {
+    let (value, country_code) = s.rsplit_once(',')?;
+    let (value, country_code) = (value.trim(), country_code.trim());
+    if value.is_empty() || country_code.is_empty() {
+        None
+    } else {
+        Some((value, country_code))
+    }
+}
+
+fn parse_coord(s: &str) -> Option<AnyQuery> {
+    let (lat, lon) = s.split_once(',')?;
+    let lat = lat.trim().parse::<f64>().ok()?;
+    let lon = lon.trim().parse::<f64>().ok()?;
+    Some(AnyQuery::Coord(Coord::new(lat, lon)))
+}
+
+fn parse_city(s: &str) -> Option<AnyQuery> {
+    let (name, country_code) = split_country_code(s)?;
+    Some(AnyQuery::City(City::new(name, country_code)))
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        models::{City, CityId, Coord},
+        error::QueryValidationError,
+        models::{City, CityId, Coord, Zip},
         query::QueryParameter,
-        Query,
+        AnyQuery, Query,
     };
 
     fn coord_query() -> (Coord, Vec<QueryParameter>) {
@@ -96,6 +350,10 @@ mod tests {
         )
     }
 
+    fn zip_query() -> (Zip, Vec<QueryParameter>) {
+        (Zip::new("94040", "US"), vec![("zip", "94040,US".to_owned())])
+    }
+
     #[test]
     fn test_query_params() {
         let (query, expected) = coord_query();
@@ -109,6 +367,140 @@ mod tests {
 
         let (query, expected) = city_id_query();
         assert_eq!(query.query_params(), expected);
+
+        let (query, expected) = zip_query();
+        assert_eq!(query.query_params(), expected);
+    }
+
+    #[test]
+    fn only_point_queries_have_coordinates() {
+        let (coord, _) = coord_query();
+        assert_eq!(coord.coord().map(|c| (c.lat, c.lon)), Some((1.2345, 5.6789)));
+        assert_eq!(AnyQuery::from(coord).coord().map(|c| c.lat), Some(1.2345));
+
+        assert!(city_query1().0.coord().is_none());
+        assert!(AnyQuery::from(city_id_query().0).coord().is_none());
+        assert!(zip_query().0.coord().is_none());
+    }
+
+    fn parsed_params(s: &str) -> Vec<QueryParameter> {
+        s.parse::<AnyQuery>()
+            .unwrap_or_else(|e| panic!("{s:?} should parse. {e}"))
+            .query_params()
+    }
+
+    #[test]
+    fn any_query_from_str() {
+        assert_eq!(parsed_params("Paris,FR"), vec![("q", "Paris,FR".to_owned())]);
+        assert_eq!(
+            parsed_params("Springfield, IL, US"),
+            vec![("q", "Springfield, IL,US".to_owned())]
+        );
+        assert_eq!(parsed_params("id:2988507"), vec![("id", "2988507".to_owned())]);
+        assert_eq!(
+            parsed_params("48.85,2.35"),
+            vec![("lat", "48.85".to_owned()), ("lon", "2.35".to_owned())]
+        );
+        assert_eq!(
+            parsed_params(" -12.04, -77.03 "),
+            vec![("lat", "-12.04".to_owned()), ("lon", "-77.03".to_owned())]
+        );
+        assert_eq!(parsed_params("zip:94040,US"), vec![("zip", "94040,US".to_owned())]);
+        assert_eq!(
+            parsed_params("coord:48.85,2.35"),
+            vec![("lat", "48.85".to_owned()), ("lon", "2.35".to_owned())]
+        );
+        assert_eq!(parsed_params("city:1984,US"), vec![("q", "1984,US".to_owned())]);
+    }
+
+    #[test]
+    fn validation() {
+        assert!(Coord::new(48.85, 2.35).validate().is_ok());
+        assert_eq!(
+            Coord::new(200.0, 2.35).validate(),
+            Err(QueryValidationError::LatitudeOutOfRange(200.0))
+        );
+        assert_eq!(
+            Coord::new(48.85, -181.0).validate(),
+            Err(QueryValidationError::LongitudeOutOfRange(-181.0))
+        );
+        assert!(Coord::new(f64::NAN, 0.0).validate().is_err());
+
+        assert!(City::new("Paris", "FR").validate().is_ok());
+        assert!(City::new("Springfield, il", "us").validate().is_ok());
+        assert!(City::new("Aripuanã,MT", "BR").validate().is_ok());
+        assert_eq!(
+            City::new("Paris", "France").validate(),
+            Err(QueryValidationError::UnknownCountryCode("France".to_string()))
+        );
+        assert_eq!(
+            City::new("Springfield,ZZ", "US").validate(),
+            Err(QueryValidationError::UnknownSubdivision {
+                country_code: "US".to_string(),
+                subdivision: "ZZ".to_string()
+            })
+        );
+        assert_eq!(
+            City::new(" ,IL", "US").validate(),
+            Err(QueryValidationError::BlankCityName)
+        );
+
+        assert_eq!(CityId::new(0).validate(), Err(QueryValidationError::ZeroCityId));
+        assert!(CityId::new(2_988_507).validate().is_ok());
+
+        assert!(Zip::new("94040", "US").validate().is_ok());
+        assert_eq!(Zip::new("", "US").validate(), Err(QueryValidationError::BlankZipCode));
+
+        let any: AnyQuery = "zip:94040,XX".parse().unwrap();
+        assert_eq!(
+            any.validate(),
+            Err(QueryValidationError::UnknownCountryCode("XX".to_string()))
+        );
+    }
+
+    #[test]
+    fn any_query_from_str_rejects_malformed_input() {
+        for input in ["", "Paris", "id:abc", "zip:94040", "coord:north,east", ",FR"] {
+            assert!(input.parse::<AnyQuery>().is_err(), "{input:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn any_query_serde_round_trip() {
+        let parsed: Vec<AnyQuery> = serde_yaml::from_str(
+            "\
+- type: city
+  name: Paris
+  country_code: FR
+- type: city_id
+  id: 2988507
+  display_name: Paris by id
+- type: coord
+  lat: 48.85
+  lon: 2.35
+- type: zip
+  zip_code: \"94040\"
+  country_code: US
+",
+        )
+        .unwrap();
+
+        let params: Vec<Vec<QueryParameter>> = parsed.iter().map(Query::query_params).collect();
+        assert_eq!(
+            params,
+            vec![
+                vec![("q", "Paris,FR".to_owned())],
+                vec![("id", "2988507".to_owned())],
+                vec![("lat", "48.85".to_owned()), ("lon", "2.35".to_owned())],
+                vec![("zip", "94040,US".to_owned())],
+            ]
+        );
+        assert_eq!(parsed[1].to_string(), "Paris by id");
+
+        let yaml = serde_yaml::to_string(&parsed).unwrap();
+        let reparsed: Vec<AnyQuery> = serde_yaml::from_str(&yaml).unwrap();
+        let reparsed_params: Vec<Vec<QueryParameter>> = reparsed.iter().map(Query::query_params).collect();
+        assert_eq!(reparsed_params, params);
     }
 
     fn is_sync<T: Sync>(_: &T) -> bool {
@@ -135,5 +527,13 @@ mod tests {
         let (query, _) = city_id_query();
         assert!(is_sync(&query));
         assert!(is_send(&query));
+
+        let (query, _) = zip_query();
+        assert!(is_sync(&query));
+        assert!(is_send(&query));
+
+        let query = AnyQuery::from(zip_query().0);
+        assert!(is_sync(&query));
+        assert!(is_send(&query));
     }
 }
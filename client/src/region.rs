@@ -0,0 +1,429 @@
+//! Weather over an area rather than at a point: a [`Region`] is sampled into a grid of [`Coord`] queries, each is
+//! fetched, and the readings are aggregated into a [`RegionalSummary`].
+//!
+//! `OpenWeatherMap` answers a coordinate with the reading of a nearby station, so close grid points often get the
+//! same reading.  Readings are deduplicated on [`CurrentWeather::id`] before aggregating, and the stations several
+//! points snapped to are [flagged](StationReading::is_snapped); a finer resolution then only costs more calls.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use openweathermap_client::{models::BoundingBox, region::Region, Client, ClientOptions};
+//!
+//! let client = Client::new(ClientOptions::default())?;
+//! let county = Region::from(BoundingBox::new(51.28, -0.51, 51.69, 0.33));
+//! let summary = client.fetch_region(&county, 10.0).await?;
+//! println!("{:?}..{:?}, wind up to {:?}", summary.min_temp, summary.max_temp, summary.max_wind_speed);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    error::{ApiCallError, RegionError},
+    geo::EARTH_RADIUS_KM,
+    models::{BoundingBox, Coord, CurrentWeather},
+    Client,
+};
+
+/// Most grid points a region may be sampled into, so a too fine resolution doesn't spend a day's worth of calls.
+pub const MAX_GRID_POINTS: usize = 500;
+
+/// Length of a degree of latitude, km.
+const KM_PER_DEGREE: f64 = EARTH_RADIUS_KM * std::f64::consts::PI / 180.0;
+
+/// An area to sample.
+#[derive(Debug, Clone)]
+pub enum Region {
+    /// See [`BoundingBox`]
+    BoundingBox(BoundingBox),
+    /// The area inside a ring of vertices, e.g. a county boundary.  The ring is closed implicitly and must not cross
+    /// the antimeridian.
+    Polygon(Vec<Coord>),
+}
+
+impl From<BoundingBox> for Region {
+    fn from(bbox: BoundingBox) -> Self {
+        Region::BoundingBox(bbox)
+    }
+}
+
+impl Region {
+    /// The centres of a grid of cells about `resolution_km` wide covering the region, row by row from the south-west
+    /// corner.  A region smaller than a cell is sampled at its centre.  For a polygon, the grid covers its bounding
+    /// box and only the points inside are kept.
+    ///
+    /// # Errors
+    /// If the region isn't a valid area, `resolution_km` isn't positive, or more than [`MAX_GRID_POINTS`] points
+    /// would be kept.  Only the points inside a polygon count, but neither side of its bounding box may be more than
+    /// [`MAX_GRID_POINTS`] cells long.
+    pub fn grid(&self, resolution_km: f64) -> Result<Vec<Coord>, RegionError> {
+        if !(resolution_km.is_finite() && resolution_km > 0.0) {
+            return Err(invalid(format!("resolution {resolution_km} km must be positive")));
+        }
+        match self {
+            Region::BoundingBox(bbox) => {
+                check_edges(bbox)?;
+                let east = if bbox.west > bbox.east {
+                    bbox.east + 360.0
+                } else {
+                    bbox.east
+                };
+                sample(bbox.south, bbox.west, bbox.north, east, resolution_km, |_| true)
+            }
+            Region::Polygon(vertices) => {
+                let bbox = bounds(vertices)?;
+                check_edges(&bbox)?;
+                let points = sample(bbox.south, bbox.west, bbox.north, bbox.east, resolution_km, |point| {
+                    contains(vertices, point)
+                })?;
+                if points.is_empty() {
+                    #[allow(clippy::cast_precision_loss)] // a handful of vertices
+                    let count = vertices.len() as f64;
+                    let lat = vertices.iter().map(|vertex| vertex.lat).sum::<f64>() / count;
+                    let lon = vertices.iter().map(|vertex| vertex.lon).sum::<f64>() / count;
+                    return Ok(vec![Coord::new(lat, lon)]);
+                }
+                Ok(points)
+            }
+        }
+    }
+}
+
+fn invalid(message: String) -> RegionError {
+    RegionError { message }
+}
+
+fn check_edges(bbox: &BoundingBox) -> Result<(), RegionError> {
+    let latitudes = -90.0..=90.0;
+    let longitudes = -180.0..=180.0;
+    if !(latitudes.contains(&bbox.south) && latitudes.contains(&bbox.north) && bbox.south <= bbox.north) {
+        return Err(invalid(format!(
+            "latitudes {}..{} must be within -90..90, south first",
+            bbox.south, bbox.north
+        )));
+    }
+    if !(longitudes.contains(&bbox.west) && longitudes.contains(&bbox.east)) {
+        return Err(invalid(format!(
+            "longitudes {}..{} must be within -180..180",
+            bbox.west, bbox.east
+        )));
+    }
+    Ok(())
+}
+
+/// The bounding box of a polygon.
+fn bounds(vertices: &[Coord]) -> Result<BoundingBox, RegionError> {
+    if vertices.len() < 3 {
+        return Err(invalid(format!(
+            "a polygon needs at least 3 vertices, not {}",
+            vertices.len()
+        )));
+    }
+    let fold =
+        |init: f64, pick: fn(&Coord) -> f64, choose: fn(f64, f64) -> f64| vertices.iter().map(pick).fold(init, choose);
+    Ok(BoundingBox::new(
+        fold(f64::INFINITY, |vertex| vertex.lat, f64::min),
+        fold(f64::INFINITY, |vertex| vertex.lon, f64::min),
+        fold(f64::NEG_INFINITY, |vertex| vertex.lat, f64::max),
+        fold(f64::NEG_INFINITY, |vertex| vertex.lon, f64::max),
+    ))
+}
+
+/// The cell centres between the edges that `keep` accepts, `east` possibly past 180 for boxes crossing the
+/// antimeridian.
+fn sample(
+    south: f64,
+    west: f64,
+    north: f64,
+    east: f64,
+    resolution_km: f64,
+    keep: impl Fn(&Coord) -> bool,
+) -> Result<Vec<Coord>, RegionError> {
+    let too_many = || {
+        invalid(format!(
+            "a {resolution_km} km grid would have more than {MAX_GRID_POINTS} points"
+        ))
+    };
+    let rows = cells((north - south) * KM_PER_DEGREE, resolution_km).ok_or_else(too_many)?;
+    let mut points = Vec::new();
+    for row in 0..rows {
+        let lat = south + (north - south) * centre(row, rows);
+        let width_km = (east - west) * KM_PER_DEGREE * lat.to_radians().cos();
+        let columns = cells(width_km, resolution_km).ok_or_else(too_many)?;
+        for column in 0..columns {
+            let lon = west + (east - west) * centre(column, columns);
+            let point = Coord::new(lat, if lon > 180.0 { lon - 360.0 } else { lon });
+            if keep(&point) {
+                if points.len() == MAX_GRID_POINTS {
+                    return Err(too_many());
+                }
+                points.push(point);
+            }
+        }
+    }
+    Ok(points)
+}
+
+/// Number of cells about `resolution_km` wide along `length_km`, at least 1, or `None` if more than
+/// [`MAX_GRID_POINTS`].
+fn cells(length_km: f64, resolution_km: f64) -> Option<usize> {
+    let count = (length_km / resolution_km).round().max(1.0);
+    #[allow(clippy::cast_precision_loss)]
+    if count > MAX_GRID_POINTS as f64 {
+        return None;
+    }
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // between 1 and MAX_GRID_POINTS
+    Some(count as usize)
+}
+
+/// Where the centre of cell `index` of `count` is, between 0 and 1.
+fn centre(index: usize, count: usize) -> f64 {
+    #[allow(clippy::cast_precision_loss)] // at most MAX_GRID_POINTS
+    let centre = (index as f64 + 0.5) / count as f64;
+    centre
+}
+
+/// Whether `point` is inside the polygon, by counting the edges a ray going east from it crosses.
+fn contains(vertices: &[Coord], point: &Coord) -> bool {
+    let mut inside = false;
+    let mut previous = &vertices[vertices.len() - 1];
+    for vertex in vertices {
+        if (vertex.lat > point.lat) != (previous.lat > point.lat) {
+            let crossing =
+                vertex.lon + (point.lat - vertex.lat) / (previous.lat - vertex.lat) * (previous.lon - vertex.lon);
+            if point.lon < crossing {
+                inside = !inside;
+            }
+        }
+        previous = vertex;
+    }
+    inside
+}
+
+/// A station's reading, with the grid points that were answered with it.
+#[derive(Debug, Clone)]
+pub struct StationReading {
+    /// The reading
+    pub reading: CurrentWeather,
+    /// The grid points answered with this reading
+    pub grid_points: Vec<Coord>,
+}
+
+impl StationReading {
+    /// Whether `OpenWeatherMap` snapped several grid points to this station.
+    pub fn is_snapped(&self) -> bool {
+        self.grid_points.len() > 1
+    }
+}
+
+/// Aggregated readings over a [`Region`], in the client's units.  Each station counts once however many grid points
+/// it answered.
+#[derive(Debug)]
+pub struct RegionalSummary {
+    /// Number of grid points fetched
+    pub grid_points: usize,
+    /// The distinct readings, in grid order.  Readings with the id 0, away from any city, are never merged.
+    pub stations: Vec<StationReading>,
+    /// The grid points whose reading couldn't be fetched
+    pub failures: Vec<(Coord, ApiCallError)>,
+    /// Lowest temperature, `None` if no reading was fetched
+    pub min_temp: Option<f64>,
+    /// Highest temperature
+    pub max_temp: Option<f64>,
+    /// Mean temperature of the stations
+    pub mean_temp: Option<f64>,
+    /// Highest wind speed
+    pub max_wind_speed: Option<f64>,
+    /// Highest wind gust, `None` if no reading has gusts
+    pub max_wind_gust: Option<f64>,
+}
+
+impl RegionalSummary {
+    /// Aggregates the result fetched for each grid point.
+    pub fn from_results(results: impl IntoIterator<Item = (Coord, Result<CurrentWeather, ApiCallError>)>) -> Self {
+        let mut grid_points = 0;
+        let mut stations: Vec<StationReading> = Vec::new();
+        let mut by_id: HashMap<u64, usize> = HashMap::new();
+        let mut failures = Vec::new();
+        for (point, result) in results {
+            grid_points += 1;
+            match result {
+                Ok(reading) => match by_id.get(&reading.id) {
+                    Some(&index) if reading.id != 0 => stations[index].grid_points.push(point),
+                    _ => {
+                        by_id.insert(reading.id, stations.len());
+                        stations.push(StationReading {
+                            reading,
+                            grid_points: vec![point],
+                        });
+                    }
+                },
+                Err(error) => failures.push((point, error)),
+            }
+        }
+
+        let temps = || stations.iter().map(|station| station.reading.main.temp);
+        #[allow(clippy::cast_precision_loss)] // at most MAX_GRID_POINTS
+        let mean_temp = (!stations.is_empty()).then(|| temps().sum::<f64>() / stations.len() as f64);
+        RegionalSummary {
+            grid_points,
+            min_temp: temps().reduce(f64::min),
+            max_temp: temps().reduce(f64::max),
+            mean_temp,
+            max_wind_speed: stations
+                .iter()
+                .map(|station| station.reading.wind.speed)
+                .reduce(f64::max),
+            max_wind_gust: stations
+                .iter()
+                .filter_map(|station| station.reading.wind.gust)
+                .reduce(f64::max),
+            stations,
+            failures,
+        }
+    }
+
+    /// The stations several grid points snapped to.
+    pub fn snapped(&self) -> impl Iterator<Item = &StationReading> {
+        self.stations.iter().filter(|station| station.is_snapped())
+    }
+}
+
+impl Client {
+    /// Fetch, one after another, the current weather at each point of `region`'s grid (see [`Region::grid`]) and
+    /// aggregate it.  Retries and the cache apply to each call, but the client doesn't pace them: up to
+    /// [`MAX_GRID_POINTS`] calls are made as fast as the API answers, unless the client's transport rate limits them
+    /// (see [`Client::with_transport`]).  A failed point doesn't stop the others from being fetched.
+    ///
+    /// # Errors
+    /// If the region can't be sampled, see [`Region::grid`].  Failed calls are in
+    /// [`RegionalSummary::failures`].
+    pub async fn fetch_region(&self, region: &Region, resolution_km: f64) -> Result<RegionalSummary, RegionError> {
+        let mut results = Vec::new();
+        for point in region.grid(resolution_km)? {
+            let reading = self.fetch_weather(&point).await;
+            results.push((point, reading));
+        }
+        Ok(RegionalSummary::from_results(results))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::StatusCode;
+
+    #[test]
+    fn boxes_are_sampled_at_cell_centres() {
+        // About 44 km tall and 44 km wide at 45° north
+        let bbox = BoundingBox::new(45.0, 5.0, 45.4, 5.56);
+
+        let grid = Region::from(bbox).grid(22.0).unwrap();
+
+        assert_eq!(grid.len(), 4);
+        assert!((grid[0].lat - 45.1).abs() < 1e-9 && (grid[0].lon - 5.14).abs() < 1e-9);
+        assert!((grid[3].lat - 45.3).abs() < 1e-9 && (grid[3].lon - 5.42).abs() < 1e-9);
+    }
+
+    #[test]
+    fn small_regions_are_sampled_at_their_centre() {
+        let grid = Region::from(BoundingBox::new(45.0, 5.0, 45.01, 5.01))
+            .grid(10.0)
+            .unwrap();
+
+        assert_eq!(grid.len(), 1);
+        assert!((grid[0].lat - 45.005).abs() < 1e-9);
+    }
+
+    #[test]
+    fn boxes_crossing_the_antimeridian_wrap_around() {
+        let grid = Region::from(BoundingBox::new(-0.5, 179.5, 0.5, -179.5))
+            .grid(55.0)
+            .unwrap();
+
+        let longitudes: Vec<f64> = grid.iter().take(2).map(|point| point.lon).collect();
+        assert_eq!(longitudes, [180.0 - 0.25, -180.0 + 0.25]);
+    }
+
+    #[test]
+    fn polygons_keep_the_points_inside() {
+        // A right triangle with its right angle in the south-west
+        let triangle = Region::Polygon(vec![Coord::new(0.0, 0.0), Coord::new(1.0, 0.0), Coord::new(0.0, 1.0)]);
+
+        let grid = triangle.grid(11.1195).unwrap();
+
+        // Half of the 10 by 10 grid over the bounding box, give or take the points on the hypotenuse
+        assert!((45..=55).contains(&grid.len()), "{}", grid.len());
+        assert!(grid.iter().all(|point| point.lat + point.lon <= 1.0 + 1e-9));
+    }
+
+    #[test]
+    fn only_the_points_inside_a_polygon_count_towards_the_limit() {
+        let triangle = Region::Polygon(vec![Coord::new(0.0, 0.0), Coord::new(2.5, 0.0), Coord::new(0.0, 2.5)]);
+
+        // 625 points over the bounding box, about half of them inside
+        let grid = triangle.grid(11.1195).unwrap();
+        assert!((300..=330).contains(&grid.len()), "{}", grid.len());
+        assert!(triangle.grid(7.0).is_err());
+    }
+
+    #[test]
+    fn invalid_regions_are_rejected() {
+        let message = |region: Region, resolution_km| region.grid(resolution_km).unwrap_err().message;
+        let bbox = BoundingBox::new(40.0, -10.0, 50.0, 10.0);
+
+        assert_eq!(message(bbox.into(), 0.0), "resolution 0 km must be positive");
+        assert_eq!(message(bbox.into(), 1.0), "a 1 km grid would have more than 500 points");
+        assert_eq!(
+            message(BoundingBox::new(50.0, 0.0, 40.0, 1.0).into(), 10.0),
+            "latitudes 50..40 must be within -90..90, south first"
+        );
+        assert_eq!(
+            message(Region::Polygon(vec![Coord::new(0.0, 0.0), Coord::new(1.0, 1.0)]), 10.0),
+            "a polygon needs at least 3 vertices, not 2"
+        );
+    }
+
+    #[test]
+    fn stations_are_deduplicated_before_aggregating() {
+        let reading = |id, temp, wind| CurrentWeather::builder().city_id(id).temp(temp).wind(wind, 0.0).build();
+        let point = |lat| Coord::new(lat, 0.0);
+
+        let summary = RegionalSummary::from_results([
+            (point(1.0), Ok(reading(1, 10.0, 3.0))),
+            (point(2.0), Ok(reading(1, 10.0, 3.0))),
+            (point(3.0), Ok(reading(1, 10.0, 3.0))),
+            (point(4.0), Ok(reading(2, 16.0, 8.0))),
+            (point(5.0), Ok(reading(0, 7.0, 1.0))),
+            (point(6.0), Ok(reading(0, 7.0, 1.0))),
+            (
+                point(7.0),
+                Err(ApiCallError::InvalidResponsStatus {
+                    status: StatusCode::TOO_MANY_REQUESTS,
+                    body: String::new(),
+                }),
+            ),
+        ]);
+
+        assert_eq!(summary.grid_points, 7);
+        assert_eq!(summary.stations.len(), 4);
+        assert_eq!(summary.failures.len(), 1);
+        let snapped: Vec<usize> = summary.snapped().map(|station| station.grid_points.len()).collect();
+        assert_eq!(snapped, [3]);
+        assert_eq!(summary.min_temp, Some(7.0));
+        assert_eq!(summary.max_temp, Some(16.0));
+        assert_eq!(summary.mean_temp, Some(10.0));
+        assert_eq!(summary.max_wind_speed, Some(8.0));
+        assert_eq!(summary.max_wind_gust, None);
+    }
+
+    #[test]
+    fn nothing_is_aggregated_without_readings() {
+        let summary = RegionalSummary::from_results([]);
+
+        assert_eq!(summary.grid_points, 0);
+        assert_eq!(summary.mean_temp, None);
+        assert_eq!(summary.max_wind_speed, None);
+    }
+}
@@ -0,0 +1,169 @@
+use hyper::{Method, StatusCode};
+use std::time::Duration;
+
+use crate::error::ApiCallError;
+
+/// How the client retries calls that failed for a possibly transient reason: no response at all, a
+/// `429 Too Many Requests` or a `5xx` status.  Only `GET`, `HEAD` and `DELETE` calls are retried unless
+/// `retry_non_idempotent` is set.  See [`ClientBuilder::retry`](crate::ClientBuilder::retry).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts made in total, including the first one.  1 disables retries.
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubled for each following one.
+    pub initial_backoff: Duration,
+    /// Longest wait between two attempts, including waits requested by a `Retry-After` header.
+    pub max_backoff: Duration,
+    /// Also retry `POST` and `PUT` calls.  A call that failed without a response may still have been applied, so a
+    /// retry can e.g. create a station or polygon twice.
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    /// 3 attempts, waiting 500ms then 1s, at most 30s, of `GET`, `HEAD` and `DELETE` calls only.
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+/// Why and how long to wait before the next attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RetryDelay {
+    /// The API asked for the wait with a `429` response's `Retry-After` header.
+    RateLimited(Duration),
+    /// Exponential backoff.
+    Backoff(Duration),
+}
+
+impl RetryPolicy {
+    /// The wait before retrying a `method` call whose `attempt` failed with `error`, or `None` if it shouldn't be
+    /// retried.
+    pub(crate) fn delay(
+        &self,
+        method: &Method,
+        attempt: u32,
+        error: &ApiCallError,
+        retry_after: Option<Duration>,
+    ) -> Option<RetryDelay> {
+        if attempt >= self.max_attempts || !is_transient(error) {
+            return None;
+        }
+        if !self.retry_non_idempotent && !matches!(*method, Method::GET | Method::HEAD | Method::DELETE) {
+            return None;
+        }
+        if let Some(wait) = retry_after {
+            return Some(RetryDelay::RateLimited(wait.min(self.max_backoff)));
+        }
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)));
+        Some(RetryDelay::Backoff(backoff.min(self.max_backoff)))
+    }
+
+    pub(crate) fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.max_attempts == 0 {
+            problems.push("retry.max_attempts must be at least 1".to_string());
+        }
+        if self.initial_backoff > self.max_backoff {
+            problems.push("retry.initial_backoff must not exceed retry.max_backoff".to_string());
+        }
+        problems
+    }
+}
+
+fn is_transient(error: &ApiCallError) -> bool {
+    match error {
+        ApiCallError::HttpError { .. } => true,
+        ApiCallError::InvalidResponsStatus { status, .. } => {
+            *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(status: StatusCode) -> ApiCallError {
+        ApiCallError::InvalidResponsStatus {
+            status,
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+            ..RetryPolicy::default()
+        };
+        let error = status(StatusCode::BAD_GATEWAY);
+
+        let delays: Vec<_> = (1..=4)
+            .map(|attempt| policy.delay(&Method::GET, attempt, &error, None))
+            .collect();
+
+        assert_eq!(
+            delays,
+            [1, 2, 4, 5].map(|secs| Some(RetryDelay::Backoff(Duration::from_secs(secs))))
+        );
+    }
+
+    #[test]
+    fn only_transient_failures_are_retried_until_attempts_run_out() {
+        let policy = RetryPolicy::default();
+
+        assert!(policy
+            .delay(&Method::GET, 1, &status(StatusCode::SERVICE_UNAVAILABLE), None)
+            .is_some());
+        assert!(policy
+            .delay(&Method::GET, 1, &status(StatusCode::NOT_FOUND), None)
+            .is_none());
+        assert!(policy
+            .delay(&Method::GET, 1, &status(StatusCode::UNAUTHORIZED), None)
+            .is_none());
+        assert!(policy
+            .delay(&Method::GET, 3, &status(StatusCode::SERVICE_UNAVAILABLE), None)
+            .is_none());
+    }
+
+    #[test]
+    fn only_idempotent_calls_are_retried_unless_asked_for() {
+        let error = status(StatusCode::BAD_GATEWAY);
+        let policy = RetryPolicy::default();
+
+        assert!(policy.delay(&Method::DELETE, 1, &error, None).is_some());
+        assert!(policy.delay(&Method::POST, 1, &error, None).is_none());
+        assert!(policy.delay(&Method::PUT, 1, &error, None).is_none());
+
+        let policy = RetryPolicy {
+            retry_non_idempotent: true,
+            ..RetryPolicy::default()
+        };
+        assert!(policy.delay(&Method::POST, 1, &error, None).is_some());
+    }
+
+    #[test]
+    fn retry_after_is_honoured_within_the_maximum() {
+        let policy = RetryPolicy::default();
+        let error = status(StatusCode::TOO_MANY_REQUESTS);
+
+        assert_eq!(
+            policy.delay(&Method::GET, 1, &error, Some(Duration::from_secs(2))),
+            Some(RetryDelay::RateLimited(Duration::from_secs(2)))
+        );
+        assert_eq!(
+            policy.delay(&Method::GET, 1, &error, Some(Duration::from_secs(3600))),
+            Some(RetryDelay::RateLimited(Duration::from_secs(30)))
+        );
+    }
+}
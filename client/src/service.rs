@@ -0,0 +1,173 @@
+//! [`tower`] integration, behind the `tower` feature.  The [`Client`] is a `Service<WeatherRequest>`, so it can be
+//! wrapped in standard middleware, and any `tower` HTTP service can be its [`Transport`] through [`TowerTransport`].
+//! [`HyperTransport`] is itself such a service, so middleware can also be layered under the client.  Time spent in
+//! middleware under the client counts towards the latency it reports (see
+//! [`ClientObserver::on_response`](crate::ClientObserver::on_response)), so layer rate limits and other waits
+//! above it:
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use openweathermap_client::{service::TowerTransport, transport::HyperTransport, Client, ClientOptions};
+//! use std::{sync::Arc, time::Duration};
+//! use tower::ServiceBuilder;
+//!
+//! let http = ServiceBuilder::new()
+//!     .timeout(Duration::from_secs(10))
+//!     .service(HyperTransport::new()?);
+//! let client = Client::with_transport(ClientOptions::default(), Arc::new(TowerTransport::new(http)))?;
+//! # Ok(())
+//! # }
+//! ```
+
+use hyper::{body::Bytes, Request, Response};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tower::{Service, ServiceExt};
+
+use crate::{
+    error::ApiCallError,
+    models::CurrentWeather,
+    transport::{HyperTransport, Transport, TransportError, TransportFuture},
+    AnyQuery, Client,
+};
+
+/// A request for the current weather, see [`Client::fetch_weather`].
+#[derive(Debug, Clone)]
+pub struct WeatherRequest {
+    /// What to fetch the weather for
+    pub query: AnyQuery,
+}
+
+impl WeatherRequest {
+    /// The current weather for `query`.
+    pub fn new(query: impl Into<AnyQuery>) -> Self {
+        WeatherRequest { query: query.into() }
+    }
+}
+
+impl From<AnyQuery> for WeatherRequest {
+    fn from(query: AnyQuery) -> Self {
+        WeatherRequest { query }
+    }
+}
+
+impl Service<WeatherRequest> for Client {
+    type Response = CurrentWeather;
+    type Error = ApiCallError;
+    type Future = Pin<Box<dyn Future<Output = Result<CurrentWeather, ApiCallError>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: WeatherRequest) -> Self::Future {
+        let client = self.clone();
+        Box::pin(async move { client.fetch_weather(&request.query).await })
+    }
+}
+
+impl Service<Request<Bytes>> for HyperTransport {
+    type Response = Response<Bytes>;
+    type Error = TransportError;
+    type Future = TransportFuture<'static>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<Bytes>) -> Self::Future {
+        let transport = self.clone();
+        Box::pin(async move { transport.send(request).await })
+    }
+}
+
+/// A [`Transport`] over a `tower` HTTP service.  Each request is sent on a clone of the service, so middleware that
+/// keeps state across requests (e.g. `RateLimit`) must sit behind a `Buffer`.
+#[derive(Debug, Clone)]
+pub struct TowerTransport<S> {
+    service: S,
+}
+
+impl<S> TowerTransport<S> {
+    /// Sends requests with `service`.
+    pub fn new(service: S) -> Self {
+        TowerTransport { service }
+    }
+}
+
+impl<S> Transport for TowerTransport<S>
+where
+    S: Service<Request<Bytes>, Response = Response<Bytes>> + Clone + Send + Sync + 'static,
+    S::Error: Into<TransportError>,
+    S::Future: Send,
+{
+    fn send(&self, request: Request<Bytes>) -> TransportFuture<'_> {
+        let service = self.service.clone();
+        Box::pin(async move { service.oneshot(request).await.map_err(Into::into) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{City, PARIS},
+        ClientOptions,
+    };
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+    use tower::{service_fn, ServiceBuilder};
+
+    fn options() -> ClientOptions {
+        ClientOptions {
+            api_key: "TOWER_KEY".to_string(),
+            ..ClientOptions::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn client_is_a_weather_service_over_a_tower_transport() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = calls.clone();
+        let http = service_fn(move |_: Request<Bytes>| {
+            counted.fetch_add(1, Ordering::SeqCst);
+            async { Ok::<_, TransportError>(Response::new(Bytes::from(PARIS))) }
+        });
+        let client = Client::with_transport(options(), Arc::new(TowerTransport::new(http))).unwrap();
+
+        let mut service = ServiceBuilder::new().timeout(Duration::from_secs(5)).service(client);
+        let reading = service
+            .ready()
+            .await
+            .unwrap()
+            .call(WeatherRequest::new(City::new("Paris", "FR")))
+            .await
+            .unwrap();
+
+        assert_eq!(reading.name, "Paris");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn middleware_errors_become_transport_errors() {
+        let http = ServiceBuilder::new()
+            .timeout(Duration::from_millis(10))
+            .service(service_fn(|_: Request<Bytes>| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok::<_, TransportError>(Response::new(Bytes::new()))
+            }));
+        let client = Client::with_transport(options(), Arc::new(TowerTransport::new(http))).unwrap();
+
+        let error = client.fetch_weather(&City::new("Paris", "FR")).await.unwrap_err();
+
+        assert!(matches!(error, ApiCallError::HttpError { .. }));
+    }
+}
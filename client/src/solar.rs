@@ -0,0 +1,240 @@
+//! Clear-sky and cloudy-sky irradiance from the [Solar Irradiance API](https://openweathermap.org/api/solar-energy-prediction).
+//! Requires a paid subscription.
+
+use serde::Deserialize;
+
+use crate::{
+    error::ApiCallError,
+    hosts::{ApiHost, Endpoint},
+    models::Coord,
+    Client, RawResponse,
+};
+
+/// Api docs are here <https://openweathermap.org/api/solar-energy-prediction>
+const SOLAR_IRRADIANCE: Endpoint = Endpoint::new("solar_irradiance", ApiHost::Api, "/energy/1.0/solar/data");
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// The granularity of the irradiance readings to request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SolarInterval {
+    /// 96 readings per day
+    FifteenMinutes,
+    /// 24 readings per day
+    Hourly,
+    /// One reading per day
+    Daily,
+}
+
+impl SolarInterval {
+    fn param(self) -> &'static str {
+        match self {
+            SolarInterval::FifteenMinutes => "15m",
+            SolarInterval::Hourly => "1h",
+            SolarInterval::Daily => "1d",
+        }
+    }
+}
+
+/// Irradiance on a surface, W/m².
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct IrradianceValues {
+    /// Global horizontal irradiance
+    pub ghi: f64,
+    /// Direct normal irradiance
+    pub dni: f64,
+    /// Diffuse horizontal irradiance
+    pub dhi: f64,
+}
+
+/// Irradiance over one interval, both as if the sky were clear and taking the forecast cloud cover into account.
+#[derive(Debug, Deserialize)]
+pub struct IrradiancePeriod {
+    /// Hour of the day (local time) the interval starts at.  Absent for daily readings.
+    pub hour: Option<u8>,
+    /// Minute of the hour the interval starts at.  Only present for 15 minute readings.
+    pub minute: Option<u8>,
+    /// Irradiance under a clear sky
+    pub clear_sky: IrradianceValues,
+    /// Irradiance under the actual (or forecast) cloud cover
+    pub cloudy_sky: IrradianceValues,
+}
+
+/// The readings of one day, at the requested [`SolarInterval`].
+#[derive(Debug, Deserialize)]
+pub struct Irradiance {
+    /// The daily sums, in Wh/m²
+    #[serde(default)]
+    pub daily: Vec<IrradiancePeriod>,
+    /// The hourly readings
+    #[serde(default)]
+    pub hourly: Vec<IrradiancePeriod>,
+    /// The 15 minute readings
+    #[serde(default, rename(deserialize = "15_min"))]
+    pub fifteen_minutes: Vec<IrradiancePeriod>,
+}
+
+/// Response from the Solar Irradiance API.
+#[derive(Debug, Deserialize)]
+pub struct SolarIrradianceResponse {
+    /// Geo location, latitude
+    pub lat: f64,
+    /// Geo location, longitude
+    pub lon: f64,
+    /// The day, formatted `YYYY-MM-DD`
+    pub date: String,
+    /// Offset of local time from UTC, formatted `±HH:MM`
+    pub tz: Option<String>,
+    /// Sunrise, local time, ISO 8601
+    pub sunrise: Option<String>,
+    /// Sunset, local time, ISO 8601
+    pub sunset: Option<String>,
+    /// See [`Irradiance`]
+    pub irradiance: Irradiance,
+}
+
+impl SolarIrradianceResponse {
+    /// The offset of local time from UTC in seconds, parsed from `tz`.
+    pub fn utc_offset_seconds(&self) -> Option<i64> {
+        let tz = self.tz.as_deref()?;
+        let (sign, hours_minutes) = match tz.strip_prefix('-') {
+            Some(rest) => (-1, rest),
+            None => (1, tz.strip_prefix('+').unwrap_or(tz)),
+        };
+        let (hours, minutes) = hours_minutes.split_once(':')?;
+        Some(sign * (hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60))
+    }
+
+    /// The hourly reading for the (local) hour containing the unix time `at`.
+    pub fn hourly_at(&self, at: i64) -> Option<&IrradiancePeriod> {
+        let local = at + self.utc_offset_seconds().unwrap_or(0);
+        let hour = u8::try_from(local.rem_euclid(SECONDS_PER_DAY) / 3600).ok()?;
+        self.irradiance.hourly.iter().find(|p| p.hour == Some(hour))
+    }
+}
+
+impl Client {
+    /// Fetch the irradiance on the day containing the unix time `date`, in the location's local time.  The API
+    /// only takes local days, so the location's offset from UTC must be given, e.g. the
+    /// [`timezone`](crate::models::CurrentWeather::timezone) of a reading there.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_solar_irradiance(
+        &self,
+        coord: &Coord,
+        date: i64,
+        utc_offset_seconds: i64,
+        interval: SolarInterval,
+    ) -> Result<SolarIrradianceResponse, ApiCallError> {
+        let params = vec![
+            ("date", local_date(date, utc_offset_seconds)),
+            ("interval", interval.param().to_string()),
+        ];
+        self.get(SOLAR_IRRADIANCE, coord, params).await
+    }
+
+    /// Fetch the irradiance like [`fetch_solar_irradiance`](Client::fetch_solar_irradiance) without parsing it.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_solar_irradiance_raw(
+        &self,
+        coord: &Coord,
+        date: i64,
+        utc_offset_seconds: i64,
+        interval: SolarInterval,
+    ) -> Result<RawResponse, ApiCallError> {
+        let params = vec![
+            ("date", local_date(date, utc_offset_seconds)),
+            ("interval", interval.param().to_string()),
+        ];
+        self.get_raw(SOLAR_IRRADIANCE, coord, params).await
+    }
+}
+
+/// Formats the day containing the unix time `at`, where local time is `utc_offset_seconds` ahead of UTC, as
+/// `YYYY-MM-DD`.  This is the day [`Client::fetch_solar_irradiance`] asks for.
+#[must_use]
+pub fn local_date(at: i64, utc_offset_seconds: i64) -> String {
+    utc_date(at + utc_offset_seconds)
+}
+
+/// Formats the UTC day containing the unix time `at` as `YYYY-MM-DD`.
+fn utc_date(at: i64) -> String {
+    // Howard Hinnant's days-to-civil algorithm, see https://howardhinnant.github.io/date_algorithms.html
+    let days = at.div_euclid(SECONDS_PER_DAY) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_utc_dates() {
+        assert_eq!(utc_date(0), "1970-01-01");
+        assert_eq!(utc_date(951_782_400), "2000-02-29");
+        assert_eq!(utc_date(1_680_220_799), "2023-03-30");
+        assert_eq!(utc_date(1_680_220_800), "2023-03-31");
+        assert_eq!(utc_date(-1), "1969-12-31");
+    }
+
+    #[test]
+    fn parse_response_and_find_the_current_hour() {
+        let values =
+            r#"{"clear_sky":{"ghi":500.1,"dni":800.2,"dhi":90.3},"cloudy_sky":{"ghi":250.4,"dni":300.5,"dhi":120.6}}"#;
+        let hourly: Vec<String> = (0..24)
+            .map(|hour| format!(r#"{{"hour":{hour},"clear_sky":{{"ghi":{hour},"dni":0,"dhi":0}},"cloudy_sky":{{"ghi":{hour},"dni":0,"dhi":0}}}}"#))
+            .collect();
+        let parsed: SolarIrradianceResponse = serde_yaml::from_str(&format!(
+            r#"{{"lat":60.45,"lon":-38.67,"date":"2023-03-30","interval":"1h","tz":"+03:00",
+                "sunrise":"2023-03-30T06:04:05","sunset":"2023-03-30T18:45:23",
+                "irradiance":{{"daily":[{values}],"hourly":[{}]}}}}"#,
+            hourly.join(",")
+        ))
+        .unwrap();
+
+        assert_eq!(parsed.utc_offset_seconds(), Some(3 * 3600));
+        assert!((parsed.irradiance.daily[0].cloudy_sky.ghi - 250.4).abs() < f64::EPSILON);
+        assert!(parsed.irradiance.fifteen_minutes.is_empty());
+
+        // 2023-03-30T10:30:00Z is 13:30 local
+        let period = parsed.hourly_at(1_680_172_200).unwrap();
+        assert_eq!(period.hour, Some(13));
+    }
+
+    #[test]
+    fn the_date_asked_for_is_the_local_one() {
+        // 2023-03-30T20:30:00Z is 2023-03-31T06:30 in UTC+10, and 2023-03-30T10:30 in UTC-10
+        let at = 1_680_208_200;
+        assert_eq!(local_date(at, 10 * 3600), "2023-03-31");
+        assert_eq!(local_date(at, 0), "2023-03-30");
+        assert_eq!(local_date(1_680_220_800 + 3600, -10 * 3600), "2023-03-30");
+
+        let hourly: Vec<String> = (0..24)
+            .map(|hour| format!(r#"{{"hour":{hour},"clear_sky":{{"ghi":{hour},"dni":0,"dhi":0}},"cloudy_sky":{{"ghi":{hour},"dni":0,"dhi":0}}}}"#))
+            .collect();
+        let parsed: SolarIrradianceResponse = serde_yaml::from_str(&format!(
+            r#"{{"lat":-33.87,"lon":151.21,"date":"2023-03-31","interval":"1h","tz":"+10:00",
+                "irradiance":{{"hourly":[{}]}}}}"#,
+            hourly.join(",")
+        ))
+        .unwrap();
+
+        assert_eq!(parsed.date, local_date(at, parsed.utc_offset_seconds().unwrap()));
+        assert_eq!(parsed.hourly_at(at).unwrap().hour, Some(6));
+    }
+}
@@ -0,0 +1,353 @@
+//! Personal weather stations and their measurements with the [Weather Stations API](https://openweathermap.org/stations).
+
+use hyper::Method;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::{
+    client::ApiRequest,
+    error::ApiCallError,
+    hosts::{ApiHost, Endpoint},
+    Client,
+};
+
+/// Api docs are here <https://openweathermap.org/stations>
+const V30_STATIONS: Endpoint = Endpoint::new("stations", ApiHost::Api, "/data/3.0/stations");
+const V30_MEASUREMENTS: Endpoint = Endpoint::new("measurements", ApiHost::Api, "/data/3.0/measurements");
+
+/// The details of a station to register or update.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StationRegistration {
+    /// Your own ID for the station
+    pub external_id: String,
+    /// Name of the station
+    pub name: String,
+    /// Geo location, latitude
+    pub latitude: f64,
+    /// Geo location, longitude
+    pub longitude: f64,
+    /// Height above sea level, meter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub altitude: Option<f64>,
+}
+
+/// A station registered with the API.
+#[derive(Debug, Deserialize)]
+pub struct Station {
+    /// Station ID, assigned by the API.  Measurements are posted against it.
+    #[serde(alias = "ID")]
+    pub id: String,
+    /// Your own ID for the station
+    pub external_id: Option<String>,
+    /// Name of the station
+    pub name: String,
+    /// Geo location, latitude
+    pub latitude: f64,
+    /// Geo location, longitude
+    pub longitude: f64,
+    /// Height above sea level, meter
+    pub altitude: Option<f64>,
+    /// Internal parameter
+    pub rank: Option<i64>,
+    /// Time of registration, ISO 8601, UTC
+    pub created_at: Option<String>,
+    /// Time of the last update, ISO 8601, UTC
+    pub updated_at: Option<String>,
+}
+
+/// A layer of clouds observed by a station.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CloudLayer {
+    /// Cloud coverage code, e.g. `FEW`, `SCT`, `BKN`, `OVC`
+    pub condition: String,
+    /// Height of the cloud base, meter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance: Option<f64>,
+    /// Cumulus type, e.g. `CB` or `TCU`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cumulus: Option<String>,
+}
+
+/// One reading of a station.  Any measurement the station doesn't take can be left out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Measurement {
+    /// The [`Station::id`]
+    pub station_id: String,
+    /// Time of the reading, unix, UTC (in seconds)
+    pub dt: i64,
+    /// Air temperature, Celsius
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// Wind speed, meter/sec
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wind_speed: Option<f64>,
+    /// Wind gust, meter/sec
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wind_gust: Option<f64>,
+    /// Wind direction, degrees (meteorological)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wind_deg: Option<f64>,
+    /// Atmospheric pressure, hPa
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<f64>,
+    /// Humidity, %
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub humidity: Option<f64>,
+    /// Rain volume for the last hour, mm
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rain_1h: Option<f64>,
+    /// Rain volume for the last 6 hours, mm
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rain_6h: Option<f64>,
+    /// Rain volume for the last 24 hours, mm
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rain_24h: Option<f64>,
+    /// Snow volume for the last hour, mm
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snow_1h: Option<f64>,
+    /// Snow volume for the last 6 hours, mm
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snow_6h: Option<f64>,
+    /// Snow volume for the last 24 hours, mm
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snow_24h: Option<f64>,
+    /// Dew point, Celsius
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dew_point: Option<f64>,
+    /// See [`CloudLayer`]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub clouds: Vec<CloudLayer>,
+}
+
+/// The period each aggregated measurement covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Granularity {
+    /// One minute
+    Minute,
+    /// One hour
+    Hour,
+    /// One day
+    Day,
+}
+
+impl fmt::Display for Granularity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            Granularity::Minute => "m",
+            Granularity::Hour => "h",
+            Granularity::Day => "d",
+        };
+        write!(f, "{code}")
+    }
+}
+
+/// Statistics of a measurement over the period.
+#[derive(Debug, Deserialize)]
+pub struct AggregatedValue {
+    /// Maximum value
+    pub max: Option<f64>,
+    /// Minimum value
+    pub min: Option<f64>,
+    /// Average value
+    pub average: Option<f64>,
+    /// Number of readings averaged
+    pub weight: Option<f64>,
+}
+
+/// Wind over the period.
+#[derive(Debug, Deserialize)]
+pub struct AggregatedWind {
+    /// Wind direction, degrees (meteorological)
+    pub deg: Option<f64>,
+    /// Wind speed, meter/sec
+    pub speed: Option<f64>,
+}
+
+/// Precipitation over the period.
+#[derive(Debug, Deserialize)]
+pub struct AggregatedPrecipitation {
+    /// Rain volume, mm
+    pub rain: Option<f64>,
+    /// Snow volume, mm
+    pub snow: Option<f64>,
+}
+
+/// The measurements of a station aggregated over one period.
+#[derive(Debug, Deserialize)]
+pub struct AggregatedMeasurement {
+    /// `m`, `h` or `d`, see [`Granularity`]
+    #[serde(rename = "type")]
+    pub granularity: String,
+    /// Start of the period, unix, UTC (in seconds)
+    pub date: i64,
+    /// The [`Station::id`]
+    pub station_id: String,
+    /// Air temperature, Celsius
+    pub temp: Option<AggregatedValue>,
+    /// Humidity, %
+    pub humidity: Option<AggregatedValue>,
+    /// See [`AggregatedWind`]
+    pub wind: Option<AggregatedWind>,
+    /// Atmospheric pressure, hPa
+    pub pressure: Option<AggregatedValue>,
+    /// See [`AggregatedPrecipitation`]
+    pub precipitation: Option<AggregatedPrecipitation>,
+}
+
+impl Client {
+    /// Register a station.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn create_station(&self, station: &StationRegistration) -> Result<Station, ApiCallError> {
+        let request =
+            ApiRequest::new(V30_STATIONS, &station.external_id, Vec::new()).with_json_body(Method::POST, station)?;
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+
+    /// Fetch every station registered with the api key.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn list_stations(&self) -> Result<Vec<Station>, ApiCallError> {
+        let request = ApiRequest::new(V30_STATIONS, "all", Vec::new());
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+
+    /// Fetch one station.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_station(&self, station_id: &str) -> Result<Station, ApiCallError> {
+        let request = station_request(station_id)?;
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+
+    /// Replace the details of a station.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn update_station(
+        &self,
+        station_id: &str,
+        station: &StationRegistration,
+    ) -> Result<Station, ApiCallError> {
+        let request = station_request(station_id)?.with_json_body(Method::PUT, station)?;
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+
+    /// Remove a station and its measurements.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn delete_station(&self, station_id: &str) -> Result<(), ApiCallError> {
+        let mut request = station_request(station_id)?;
+        request.method = Method::DELETE;
+        self.call(&request).await?;
+        Ok(())
+    }
+
+    /// Upload a batch of measurements, possibly from several stations.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn post_measurements(&self, measurements: &[Measurement]) -> Result<(), ApiCallError> {
+        let subject = format!("{} measurements", measurements.len());
+        let request =
+            ApiRequest::new(V30_MEASUREMENTS, &subject, Vec::new()).with_json_body(Method::POST, &measurements)?;
+        self.call(&request).await?;
+        Ok(())
+    }
+
+    /// Fetch the measurements of a station between `from` and `to` (unix, UTC, in seconds), aggregated by
+    /// `granularity`, at most `limit` of them.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_measurements(
+        &self,
+        station_id: &str,
+        granularity: Granularity,
+        from: i64,
+        to: i64,
+        limit: u32,
+    ) -> Result<Vec<AggregatedMeasurement>, ApiCallError> {
+        let params = vec![
+            ("station_id", station_id.to_string()),
+            ("type", granularity.to_string()),
+            ("limit", limit.to_string()),
+            ("from", from.to_string()),
+            ("to", to.to_string()),
+        ];
+        let request = ApiRequest::new(V30_MEASUREMENTS, station_id, params);
+        let response = self.call(&request).await?;
+        self.parse(&request, &response)
+    }
+}
+
+fn station_request(station_id: &str) -> Result<ApiRequest, ApiCallError> {
+    ApiRequest::resource(V30_STATIONS, "station", station_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measurements_leave_out_what_was_not_measured() {
+        let measurement = Measurement {
+            station_id: "583436dd9643a9000196b8d6".to_string(),
+            dt: 1_479_817_340,
+            temperature: Some(18.7),
+            clouds: vec![CloudLayer {
+                condition: "NSC".to_string(),
+                distance: None,
+                cumulus: None,
+            }],
+            ..Measurement::default()
+        };
+
+        assert_eq!(
+            serde_json::to_string(&measurement).unwrap(),
+            r#"{"station_id":"583436dd9643a9000196b8d6","dt":1479817340,"temperature":18.7,"clouds":[{"condition":"NSC"}]}"#
+        );
+    }
+
+    #[test]
+    fn parse_stations() {
+        let created: Station = serde_yaml::from_str(
+            r#"{"ID":"583436dd9643a9000196b8d6","updated_at":"2016-11-22T12:15:25.967Z","created_at":"2016-11-22T12:15:25.967Z",
+                "user_id":"557066d0ff7a7e3897531d94","external_id":"SF_TEST001","name":"San Francisco Test Station",
+                "latitude":37.76,"longitude":-122.43,"altitude":150,"source_type":5}"#,
+        )
+        .unwrap();
+        assert_eq!(created.id, "583436dd9643a9000196b8d6");
+        assert_eq!(created.altitude, Some(150.0));
+
+        let listed: Vec<Station> = serde_yaml::from_str(
+            r#"[{"id":"583436dd9643a9000196b8d6","created_at":"2016-11-22T12:15:25.967Z","updated_at":"2016-11-22T12:15:25.967Z",
+                 "external_id":"SF_TEST001","name":"San Francisco Test Station","longitude":-122.43,"latitude":37.76,
+                 "altitude":150,"rank":0}]"#,
+        )
+        .unwrap();
+        assert_eq!(listed[0].external_id.as_deref(), Some("SF_TEST001"));
+    }
+
+    #[test]
+    fn parse_aggregated_measurements() {
+        let parsed: Vec<AggregatedMeasurement> = serde_yaml::from_str(
+            r#"[{"type":"h","date":1479816000,"station_id":"583436dd9643a9000196b8d6",
+                 "temp":{"max":18.7,"min":18.7,"average":18.7,"weight":1},"humidity":{"average":81,"weight":1},
+                 "wind":{},"pressure":{},"precipitation":{}}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(parsed[0].granularity, Granularity::Hour.to_string());
+        assert_eq!(parsed[0].temp.as_ref().and_then(|t| t.max), Some(18.7));
+        assert!(parsed[0].wind.as_ref().is_some_and(|w| w.speed.is_none()));
+    }
+}
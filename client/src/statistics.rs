@@ -0,0 +1,279 @@
+//! Climate normals from the [Statistical Weather Data API](https://openweathermap.org/api/statistics-api) and
+//! accumulated temperature and precipitation from the
+//! [Accumulated Parameters API](https://openweathermap.org/api/accumulated-parameters).  Both require a paid
+//! subscription.
+
+use serde::Deserialize;
+
+use crate::{
+    error::ApiCallError,
+    hosts::{ApiHost, Endpoint},
+    Client, Query,
+};
+
+/// Api docs are here <https://openweathermap.org/api/statistics-api>
+const V25_AGGREGATED_YEAR: Endpoint = Endpoint::new("aggregated_year", ApiHost::History, "/data/2.5/aggregated/year");
+const V25_AGGREGATED_MONTH: Endpoint =
+    Endpoint::new("aggregated_month", ApiHost::History, "/data/2.5/aggregated/month");
+const V25_AGGREGATED_DAY: Endpoint = Endpoint::new("aggregated_day", ApiHost::History, "/data/2.5/aggregated/day");
+
+/// Api docs are here <https://openweathermap.org/api/accumulated-parameters>
+const V25_ACCUMULATED_TEMPERATURE: Endpoint = Endpoint::new(
+    "accumulated_temperature",
+    ApiHost::History,
+    "/data/2.5/history/accumulated_temperature",
+);
+const V25_ACCUMULATED_PRECIPITATION: Endpoint = Endpoint::new(
+    "accumulated_precipitation",
+    ApiHost::History,
+    "/data/2.5/history/accumulated_precipitation",
+);
+
+/// Envelope around the `result` of a statistical call.
+#[derive(Debug, Deserialize)]
+pub struct AggregatedResponse<T> {
+    /// Internal parameter
+    pub cod: Option<String>,
+    /// City ID
+    pub city_id: u64,
+    /// Internal parameter
+    pub calctime: Option<f64>,
+    /// The statistics
+    pub result: T,
+}
+
+/// Statistics of a measurement over all the years on record.
+#[derive(Debug, Deserialize)]
+pub struct Stats {
+    /// Minimum value
+    pub min: f64,
+    /// Maximum value
+    pub max: f64,
+    /// Median value
+    pub median: f64,
+    /// Mean value
+    pub mean: f64,
+    /// 25th percentile
+    pub p25: f64,
+    /// 75th percentile
+    pub p75: f64,
+    /// Standard deviation
+    pub st_dev: f64,
+    /// Number of measurements
+    pub num: u64,
+}
+
+/// Statistics of temperature over all the years on record.  Unit Default: Kelvin, Metric: Celsius, Imperial:
+/// Fahrenheit.
+#[derive(Debug, Deserialize)]
+pub struct TemperatureStats {
+    /// Lowest temperature on record
+    pub record_min: f64,
+    /// Highest temperature on record
+    pub record_max: f64,
+    /// Average of the daily minimum temperatures
+    pub average_min: f64,
+    /// Average of the daily maximum temperatures
+    pub average_max: f64,
+    /// Median temperature
+    pub median: f64,
+    /// Mean temperature
+    pub mean: f64,
+    /// 25th percentile
+    pub p25: f64,
+    /// 75th percentile
+    pub p75: f64,
+    /// Standard deviation
+    pub st_dev: f64,
+    /// Number of measurements
+    pub num: u64,
+}
+
+/// Climate normals for one day of the year.
+#[derive(Debug, Deserialize)]
+pub struct AggregatedDay {
+    /// Month of the year, 1-12
+    pub month: u8,
+    /// Day of the month
+    pub day: u8,
+    /// See [`TemperatureStats`]
+    pub temp: TemperatureStats,
+    /// Atmospheric pressure, hPa
+    pub pressure: Stats,
+    /// Humidity, %
+    pub humidity: Stats,
+    /// Wind speed. Unit Default: meter/sec, Metric: meter/sec, Imperial: miles/hour.
+    pub wind: Stats,
+    /// Precipitation, mm
+    pub precipitation: Stats,
+    /// Cloudiness, %
+    pub clouds: Stats,
+    /// Hours of sunshine
+    pub sunshine_hours: Option<f64>,
+}
+
+/// Climate normals for one month of the year.
+#[derive(Debug, Deserialize)]
+pub struct AggregatedMonth {
+    /// Month of the year, 1-12
+    pub month: u8,
+    /// See [`TemperatureStats`]
+    pub temp: TemperatureStats,
+    /// Atmospheric pressure, hPa
+    pub pressure: Stats,
+    /// Humidity, %
+    pub humidity: Stats,
+    /// Wind speed. Unit Default: meter/sec, Metric: meter/sec, Imperial: miles/hour.
+    pub wind: Stats,
+    /// Precipitation, mm
+    pub precipitation: Stats,
+    /// Cloudiness, %
+    pub clouds: Stats,
+    /// Hours of sunshine
+    pub sunshine_hours: Option<f64>,
+}
+
+/// Temperature accumulated above a threshold over one day.
+#[derive(Debug, Deserialize)]
+pub struct AccumulatedTemperature {
+    /// The day, formatted `YYYY-MM-DD`
+    pub date: String,
+    /// Sum of the hourly temperatures above the threshold
+    pub temp: f64,
+    /// Number of hourly measurements summed
+    pub count: u64,
+}
+
+/// Precipitation accumulated over one day.
+#[derive(Debug, Deserialize)]
+pub struct AccumulatedPrecipitation {
+    /// The day, formatted `YYYY-MM-DD`
+    pub date: String,
+    /// Sum of the hourly precipitation, mm
+    pub rain: f64,
+    /// Number of hourly measurements summed
+    pub count: u64,
+}
+
+impl Client {
+    /// Fetch climate normals for every day of the year.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_yearly_statistics(
+        &self,
+        query: &dyn Query,
+    ) -> Result<AggregatedResponse<Vec<AggregatedDay>>, ApiCallError> {
+        self.get(V25_AGGREGATED_YEAR, query, Vec::new()).await
+    }
+
+    /// Fetch climate normals for a `month` (1-12) of the year.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_monthly_statistics(
+        &self,
+        query: &dyn Query,
+        month: u8,
+    ) -> Result<AggregatedResponse<AggregatedMonth>, ApiCallError> {
+        let params = vec![("month", month.to_string())];
+        self.get(V25_AGGREGATED_MONTH, query, params).await
+    }
+
+    /// Fetch climate normals for one `day` of a `month` (1-12) of the year.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_daily_statistics(
+        &self,
+        query: &dyn Query,
+        month: u8,
+        day: u8,
+    ) -> Result<AggregatedResponse<AggregatedDay>, ApiCallError> {
+        let params = vec![("month", month.to_string()), ("day", day.to_string())];
+        self.get(V25_AGGREGATED_DAY, query, params).await
+    }
+
+    /// Fetch the daily sums of hourly temperatures above `threshold` between `start` and `end` (unix, UTC, in
+    /// seconds).
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_accumulated_temperature(
+        &self,
+        query: &dyn Query,
+        start: i64,
+        end: i64,
+        threshold: f64,
+    ) -> Result<Vec<AccumulatedTemperature>, ApiCallError> {
+        let params = vec![
+            ("start", start.to_string()),
+            ("end", end.to_string()),
+            ("threshold", threshold.to_string()),
+        ];
+        self.get(V25_ACCUMULATED_TEMPERATURE, query, params).await
+    }
+
+    /// Fetch the daily sums of hourly precipitation between `start` and `end` (unix, UTC, in seconds), optionally
+    /// only counting hours above `threshold` mm.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_accumulated_precipitation(
+        &self,
+        query: &dyn Query,
+        start: i64,
+        end: i64,
+        threshold: Option<f64>,
+    ) -> Result<Vec<AccumulatedPrecipitation>, ApiCallError> {
+        let mut params = vec![("start", start.to_string()), ("end", end.to_string())];
+        if let Some(threshold) = threshold {
+            params.push(("threshold", threshold.to_string()));
+        }
+        self.get(V25_ACCUMULATED_PRECIPITATION, query, params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATS: &str = r#"{"min":1.5,"max":9.5,"median":4,"mean":4.2,"p25":3,"p75":6,"st_dev":1.1,"num":240}"#;
+
+    fn month_json() -> String {
+        format!(
+            r#"{{"month":2,
+                "temp":{{"record_min":-15.2,"record_max":18.4,"average_min":-2.1,"average_max":9.8,"median":3.9,"mean":4.1,"p25":0.8,"p75":7.2,"st_dev":4.4,"num":672}},
+                "pressure":{STATS},"humidity":{STATS},"wind":{STATS},"precipitation":{STATS},"clouds":{STATS},
+                "sunshine_hours":95.3}}"#
+        )
+    }
+
+    #[test]
+    fn parse_monthly_statistics() {
+        let parsed: AggregatedResponse<AggregatedMonth> = serde_yaml::from_str(&format!(
+            r#"{{"cod":200,"city_id":2988507,"calctime":0.0123,"result":{}}}"#,
+            month_json()
+        ))
+        .unwrap();
+
+        assert_eq!(parsed.cod.as_deref(), Some("200"));
+        assert_eq!(parsed.city_id, 2_988_507);
+        assert_eq!(parsed.result.month, 2);
+        assert!((parsed.result.temp.average_max - 9.8).abs() < f64::EPSILON);
+        assert_eq!(parsed.result.precipitation.num, 240);
+        assert_eq!(parsed.result.sunshine_hours, Some(95.3));
+    }
+
+    #[test]
+    fn parse_accumulated_values() {
+        let temperature: Vec<AccumulatedTemperature> =
+            serde_yaml::from_str(r#"[{"date":"2019-01-01","temp":112.5,"count":24}]"#).unwrap();
+        assert_eq!(temperature[0].date, "2019-01-01");
+        assert_eq!(temperature[0].count, 24);
+
+        let precipitation: Vec<AccumulatedPrecipitation> =
+            serde_yaml::from_str(r#"[{"date":"2019-01-01","rain":3.5,"count":24}]"#).unwrap();
+        assert!((precipitation[0].rain - 3.5).abs() < f64::EPSILON);
+    }
+}
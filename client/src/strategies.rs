@@ -0,0 +1,156 @@
+//! [`proptest`] strategies generating physically plausible readings, behind the `proptest` feature, so code
+//! consuming [`CurrentWeather`] can be tested property-style instead of against a handful of JSON fixtures.  In a
+//! test module, mark the property `#[test]`:
+//!
+//! ```
+//! use openweathermap_client::{models::UnitSystem, strategies::current_weather};
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     fn humidity_is_a_percentage(reading in current_weather(UnitSystem::Metric)) {
+//!         prop_assert!((0.0..=100.0).contains(&reading.main.humidity));
+//!     }
+//! }
+//! # humidity_is_a_percentage();
+//! ```
+
+use proptest::{option, prelude::*};
+
+use crate::models::{CurrentWeather, Extra, UnitSystem, Weather, Wind};
+
+/// Start of 2000 and of 2040, unix, UTC.
+const TIMES: std::ops::Range<i64> = 946_684_800..2_208_988_800;
+
+/// Readings in `units` from anywhere on Earth, between 2000 and 2040.
+///
+/// The values stay within what's been observed (-60°C to 50°C, 950 to 1050 hPa, winds up to 40 m/s) and agree with
+/// each other: the temperature is within its minimum and maximum, gusts are faster than the wind, precipitation
+/// falls as snow below 0°C and as rain otherwise, the weather condition matches the precipitation and cloudiness,
+/// and the sun rises before it sets.  Optional fields are sometimes left out.
+pub fn current_weather(units: UnitSystem) -> impl Strategy<Value = CurrentWeather> {
+    let place = (-90.0..=90.0_f64, -180.0..=180.0_f64, -12_i64..=14);
+    let air = (
+        -60.0..50.0_f64,
+        -10.0..=5.0_f64,
+        0.0..=4.0_f64,
+        0_u8..=100,
+        950.0..1050.0_f64,
+    );
+    let wind = (0.0..40.0_f64, option::of(0.0..360.0_f64), option::of(1.0..2.0_f64));
+    let sky = (0_u8..=100, option::of(0.1..20.0_f64), option::of(0_u16..=10_000));
+    let time = (TIMES, option::of((0_i64..86_400, 1_i64..86_400)));
+    (place, air, wind, sky, time).prop_map(move |(place, air, wind, sky, time)| {
+        let (lat, lon, timezone_hours) = place;
+        let (temp, feels_like_offset, spread, humidity, pressure) = air;
+        let (wind_speed, wind_deg, gust_factor) = wind;
+        let (cloudiness, precipitation, visibility) = sky;
+        let (dt, sun) = time;
+
+        let builder = CurrentWeather::builder()
+            .coord(lat, lon)
+            .dt(dt)
+            .timezone(timezone_hours * 3600)
+            .sun(sun.map(|(sunrise, day_length)| {
+                let sunrise = dt - dt.rem_euclid(86_400) + sunrise;
+                (sunrise, sunrise + day_length)
+            }))
+            .weather(condition(temp, cloudiness, precipitation))
+            .temp(temperature(temp, units))
+            .feels_like(temperature(temp + feels_like_offset, units))
+            .temp_range(temperature(temp - spread, units), temperature(temp + spread, units))
+            .humidity(f64::from(humidity))
+            .pressure(pressure)
+            .clouds(Some(f64::from(cloudiness)))
+            .visibility(visibility);
+        let mut reading = match (precipitation, temp < 0.0) {
+            (Some(mm), true) => builder.snow_1h(mm),
+            (Some(mm), false) => builder.rain_1h(mm),
+            (None, _) => builder,
+        }
+        .build();
+        let wind_speed = speed(wind_speed, units);
+        reading.wind = Wind {
+            speed: wind_speed,
+            deg: wind_deg,
+            gust: gust_factor.map(|factor| wind_speed * factor),
+            extra: Extra::new(),
+        };
+        // Anywhere on Earth, rarely in a city
+        reading.id = 0;
+        reading.name = String::new();
+        reading.sys.country = None;
+        reading
+    })
+}
+
+/// The condition reported for the precipitation (mm in the last hour) or cloudiness.
+fn condition(temp: f64, cloudiness: u8, precipitation: Option<f64>) -> Weather {
+    match precipitation {
+        Some(mm) if temp < 0.0 && mm < 1.0 => Weather::new(600, "Snow", "light snow", "13d"),
+        Some(_) if temp < 0.0 => Weather::new(601, "Snow", "snow", "13d"),
+        Some(mm) if mm < 2.5 => Weather::new(500, "Rain", "light rain", "10d"),
+        Some(_) => Weather::new(501, "Rain", "moderate rain", "10d"),
+        None => match cloudiness {
+            0..=10 => Weather::new(800, "Clear", "clear sky", "01d"),
+            11..=25 => Weather::new(801, "Clouds", "few clouds", "02d"),
+            26..=50 => Weather::new(802, "Clouds", "scattered clouds", "03d"),
+            51..=84 => Weather::new(803, "Clouds", "broken clouds", "04d"),
+            _ => Weather::new(804, "Clouds", "overcast clouds", "04d"),
+        },
+    }
+}
+
+/// `celsius` in `units`.
+fn temperature(celsius: f64, units: UnitSystem) -> f64 {
+    match units {
+        UnitSystem::Metric => celsius,
+        UnitSystem::Standard => celsius + 273.15,
+        UnitSystem::Imperial => celsius * 1.8 + 32.0,
+    }
+}
+
+/// `meters_per_second` in `units`.
+fn speed(meters_per_second: f64, units: UnitSystem) -> f64 {
+    match units {
+        UnitSystem::Imperial => meters_per_second * 2.236_936,
+        UnitSystem::Metric | UnitSystem::Standard => meters_per_second,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{summary::Summarizer, Query};
+
+    proptest! {
+        #[test]
+        fn readings_are_consistent(reading in current_weather(UnitSystem::Metric)) {
+            prop_assert!(reading.coord.validate().is_ok());
+            prop_assert!(reading.main.temp_min <= reading.main.temp && reading.main.temp <= reading.main.temp_max);
+            prop_assert!((0.0..=100.0).contains(&reading.main.humidity));
+            if let Some(gust) = reading.wind.gust {
+                prop_assert!(gust >= reading.wind.speed);
+            }
+            if reading.snow.is_some() {
+                prop_assert!(reading.main.temp < 0.0);
+                prop_assert_eq!(&reading.weather[0].main, "Snow");
+            }
+            if let (Some(sunrise), Some(sunset)) = (reading.sys.sunrise, reading.sys.sunset) {
+                prop_assert!(sunrise < sunset);
+            }
+        }
+
+        #[test]
+        fn imperial_readings_are_converted(reading in current_weather(UnitSystem::Imperial)) {
+            prop_assert!((-76.0..122.0).contains(&reading.main.temp));
+            prop_assert!(reading.wind.speed < 90.0);
+        }
+
+        #[test]
+        fn every_reading_can_be_summarized(reading in current_weather(UnitSystem::Standard)) {
+            let summary = Summarizer::new("en", UnitSystem::Standard).one_line(&reading);
+
+            prop_assert!(summary.contains(" K"), "{}", summary);
+        }
+    }
+}
@@ -0,0 +1,564 @@
+//! Human-readable summaries of a [`CurrentWeather`] reading, short enough for chat messages and SMS.
+//!
+//! A [`Summarizer`] renders a reading into one line or several, in the language and [`UnitSystem`] the client is
+//! configured with.  The weather description comes from the API, already translated; labels, compass points and the
+//! decimal separator are built in for English, French, German, Spanish, Italian, Portuguese, Dutch, Polish and
+//! Swedish.  Other languages fall back to English labels, which custom templates can replace.
+//!
+//! Templates are plain text with these fields:
+//!
+//! | Field             | Example            |
+//! |-------------------|--------------------|
+//! | `{location}`      | `Paris, FR`        |
+//! | `{description}`   | `light rain`       |
+//! | `{temp}`          | `15°C`             |
+//! | `{feels_like}`    | `14°C`             |
+//! | `{humidity}`      | `72%`              |
+//! | `{pressure}`      | `1019 hPa`         |
+//! | `{wind}`          | `4.5 m/s WSW`      |
+//! | `{wind_speed}`    | `4.5 m/s`          |
+//! | `{wind_direction}`| `WSW`              |
+//! | `{gust}`          | `8.2 m/s`          |
+//! | `{clouds}`        | `75%`              |
+//! | `{precipitation}` | `rain 0.4 mm (1h)` |
+//!
+//! `{wind_direction}`, `{gust}`, `{clouds}` and `{precipitation}` are empty when the reading has none.  Text between `[` and `]` is left out unless
+//! every field in it has a value, e.g. `[, {precipitation}]`.  Write `{{`, `}}`, `[[` and `]]` for the characters
+//! themselves.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use openweathermap_client::{models::City, Client, ClientOptions};
+//!
+//! let client = Client::new(ClientOptions::default())?;
+//! let summarizer = client
+//!     .summarizer()
+//!     .with_one_line_template("{location}: {temp}, {description}[ ({precipitation})]")?;
+//! let reading = client.fetch_weather(&City::new("Paris", "FR")).await?;
+//! println!("{}", summarizer.one_line(&reading));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::SummaryTemplateError,
+    models::{CurrentWeather, PrecipVolume, UnitSystem},
+    Client,
+};
+
+/// Labels, compass points and default templates of a language.
+#[derive(Debug)]
+struct Language {
+    /// The `OpenWeatherMap` language codes, without a region
+    codes: &'static [&'static str],
+    decimal_comma: bool,
+    compass: [&'static str; 16],
+    rain: &'static str,
+    snow: &'static str,
+    one_line: &'static str,
+    multi_line: &'static str,
+}
+
+const ENGLISH: Language = Language {
+    codes: &["en"],
+    decimal_comma: false,
+    compass: [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW",
+    ],
+    rain: "rain",
+    snow: "snow",
+    one_line: "{location}: {description}, {temp} (feels like {feels_like}), humidity {humidity}, wind {wind}\
+               [, {precipitation}]",
+    multi_line: "{location}\n{description}\nTemperature: {temp} (feels like {feels_like})\nHumidity: {humidity}\n\
+                 Wind: {wind}[, gusts {gust}][\nPrecipitation: {precipitation}]",
+};
+
+const LANGUAGES: [Language; 9] = [
+    ENGLISH,
+    Language {
+        codes: &["fr"],
+        decimal_comma: true,
+        compass: [
+            "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSO", "SO", "OSO", "O", "ONO", "NO", "NNO",
+        ],
+        rain: "pluie",
+        snow: "neige",
+        one_line: "{location} : {description}, {temp} (ressenti {feels_like}), humidité {humidity}, vent {wind}\
+                   [, {precipitation}]",
+        multi_line: "{location}\n{description}\nTempérature : {temp} (ressenti {feels_like})\nHumidité : {humidity}\n\
+                     Vent : {wind}[, rafales {gust}][\nPrécipitations : {precipitation}]",
+    },
+    Language {
+        codes: &["de"],
+        decimal_comma: true,
+        compass: [
+            "N", "NNO", "NO", "ONO", "O", "OSO", "SO", "SSO", "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW",
+        ],
+        rain: "Regen",
+        snow: "Schnee",
+        one_line: "{location}: {description}, {temp} (gefühlt {feels_like}), Luftfeuchtigkeit {humidity}, \
+                   Wind {wind}[, {precipitation}]",
+        multi_line: "{location}\n{description}\nTemperatur: {temp} (gefühlt {feels_like})\n\
+                     Luftfeuchtigkeit: {humidity}\nWind: {wind}[, Böen {gust}][\nNiederschlag: {precipitation}]",
+    },
+    Language {
+        codes: &["es", "sp"],
+        decimal_comma: true,
+        compass: [
+            "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSO", "SO", "OSO", "O", "ONO", "NO", "NNO",
+        ],
+        rain: "lluvia",
+        snow: "nieve",
+        one_line: "{location}: {description}, {temp} (sensación {feels_like}), humedad {humidity}, viento {wind}\
+                   [, {precipitation}]",
+        multi_line: "{location}\n{description}\nTemperatura: {temp} (sensación {feels_like})\nHumedad: {humidity}\n\
+                     Viento: {wind}[, ráfagas {gust}][\nPrecipitación: {precipitation}]",
+    },
+    Language {
+        codes: &["it"],
+        decimal_comma: true,
+        compass: [
+            "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSO", "SO", "OSO", "O", "ONO", "NO", "NNO",
+        ],
+        rain: "pioggia",
+        snow: "neve",
+        one_line: "{location}: {description}, {temp} (percepita {feels_like}), umidità {humidity}, vento {wind}\
+                   [, {precipitation}]",
+        multi_line: "{location}\n{description}\nTemperatura: {temp} (percepita {feels_like})\nUmidità: {humidity}\n\
+                     Vento: {wind}[, raffiche {gust}][\nPrecipitazioni: {precipitation}]",
+    },
+    Language {
+        codes: &["pt"],
+        decimal_comma: true,
+        compass: [
+            "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSO", "SO", "OSO", "O", "ONO", "NO", "NNO",
+        ],
+        rain: "chuva",
+        snow: "neve",
+        one_line: "{location}: {description}, {temp} (sensação {feels_like}), umidade {humidity}, vento {wind}\
+                   [, {precipitation}]",
+        multi_line: "{location}\n{description}\nTemperatura: {temp} (sensação {feels_like})\nUmidade: {humidity}\n\
+                     Vento: {wind}[, rajadas {gust}][\nPrecipitação: {precipitation}]",
+    },
+    Language {
+        codes: &["nl"],
+        decimal_comma: true,
+        compass: [
+            "N", "NNO", "NO", "ONO", "O", "OZO", "ZO", "ZZO", "Z", "ZZW", "ZW", "WZW", "W", "WNW", "NW", "NNW",
+        ],
+        rain: "regen",
+        snow: "sneeuw",
+        one_line: "{location}: {description}, {temp} (voelt als {feels_like}), luchtvochtigheid {humidity}, \
+                   wind {wind}[, {precipitation}]",
+        multi_line: "{location}\n{description}\nTemperatuur: {temp} (voelt als {feels_like})\n\
+                     Luchtvochtigheid: {humidity}\nWind: {wind}[, windstoten {gust}][\nNeerslag: {precipitation}]",
+    },
+    Language {
+        codes: &["pl"],
+        decimal_comma: true,
+        compass: [
+            "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW",
+        ],
+        rain: "deszcz",
+        snow: "śnieg",
+        one_line: "{location}: {description}, {temp} (odczuwalna {feels_like}), wilgotność {humidity}, \
+                   wiatr {wind}[, {precipitation}]",
+        multi_line: "{location}\n{description}\nTemperatura: {temp} (odczuwalna {feels_like})\n\
+                     Wilgotność: {humidity}\nWiatr: {wind}[, porywy {gust}][\nOpady: {precipitation}]",
+    },
+    Language {
+        codes: &["sv", "se"],
+        decimal_comma: true,
+        compass: [
+            "N", "NNO", "NO", "ONO", "O", "OSO", "SO", "SSO", "S", "SSV", "SV", "VSV", "V", "VNV", "NV", "NNV",
+        ],
+        rain: "regn",
+        snow: "snö",
+        one_line: "{location}: {description}, {temp} (känns som {feels_like}), luftfuktighet {humidity}, \
+                   vind {wind}[, {precipitation}]",
+        multi_line: "{location}\n{description}\nTemperatur: {temp} (känns som {feels_like})\n\
+                     Luftfuktighet: {humidity}\nVind: {wind}[, byar {gust}][\nNederbörd: {precipitation}]",
+    },
+];
+
+/// The built-in language for an `OpenWeatherMap` language code such as `fr` or `pt_br`, English if there is none.
+fn language(code: &str) -> &'static Language {
+    let base = code.split(['_', '-']).next().unwrap_or_default().to_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|language| language.codes.contains(&base.as_str()))
+        .unwrap_or(&LANGUAGES[0])
+}
+
+/// The 16-point compass direction the wind blows from, in `language`, e.g. `WSW` for 240° in English.
+pub fn compass_point(degrees: f64, language_code: &str) -> &'static str {
+    language(language_code).compass[compass_index(degrees)]
+}
+
+fn compass_index(degrees: f64) -> usize {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // between 0 and 16
+    let point = (degrees.rem_euclid(360.0) / 22.5).round() as usize % 16;
+    point
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Location,
+    Description,
+    Temp,
+    FeelsLike,
+    Humidity,
+    Pressure,
+    Wind,
+    WindSpeed,
+    WindDirection,
+    Gust,
+    Clouds,
+    Precipitation,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Field> {
+        Some(match name {
+            "location" => Field::Location,
+            "description" => Field::Description,
+            "temp" => Field::Temp,
+            "feels_like" => Field::FeelsLike,
+            "humidity" => Field::Humidity,
+            "pressure" => Field::Pressure,
+            "wind" => Field::Wind,
+            "wind_speed" => Field::WindSpeed,
+            "wind_direction" => Field::WindDirection,
+            "gust" => Field::Gust,
+            "clouds" => Field::Clouds,
+            "precipitation" => Field::Precipitation,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Text(String),
+    Field(Field),
+    /// Left out unless all its fields have a value.
+    Optional(Vec<Piece>),
+}
+
+/// Parses `template` into pieces, or explains what's wrong with it.
+fn parse(template: &str) -> Result<Vec<Piece>, SummaryTemplateError> {
+    let error = |message: &str| SummaryTemplateError {
+        template: template.to_string(),
+        message: message.to_string(),
+    };
+    let mut pieces = Vec::new();
+    let mut optional: Option<Vec<Piece>> = None;
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if matches!(c, '{' | '}' | '[' | ']') && chars.peek() == Some(&c) {
+            chars.next();
+            text.push(c);
+            continue;
+        }
+        match c {
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(error("unclosed {")),
+                    }
+                }
+                let field = Field::from_name(&name).ok_or_else(|| error(&format!("unknown field {{{name}}}")))?;
+                let target = optional.as_mut().unwrap_or(&mut pieces);
+                flush(&mut text, target);
+                target.push(Piece::Field(field));
+            }
+            '}' => return Err(error("unmatched }")),
+            '[' if optional.is_some() => return Err(error("[ sections can't be nested")),
+            '[' => {
+                flush(&mut text, &mut pieces);
+                optional = Some(Vec::new());
+            }
+            ']' => {
+                let mut section = optional.take().ok_or_else(|| error("unmatched ]"))?;
+                flush(&mut text, &mut section);
+                pieces.push(Piece::Optional(section));
+            }
+            _ => text.push(c),
+        }
+    }
+    if optional.is_some() {
+        return Err(error("unclosed ["));
+    }
+    flush(&mut text, &mut pieces);
+    Ok(pieces)
+}
+
+fn flush(text: &mut String, pieces: &mut Vec<Piece>) {
+    if !text.is_empty() {
+        pieces.push(Piece::Text(std::mem::take(text)));
+    }
+}
+
+/// Renders [`CurrentWeather`] readings into text, see the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct Summarizer {
+    language: &'static Language,
+    units: UnitSystem,
+    one_line: Vec<Piece>,
+    multi_line: Vec<Piece>,
+}
+
+impl Summarizer {
+    /// Summarizes readings made in `units`, with the built-in templates of `language` (an `OpenWeatherMap` language
+    /// code such as `fr`).
+    pub fn new(language_code: &str, units: UnitSystem) -> Self {
+        let language = language(language_code);
+        Summarizer {
+            language,
+            units,
+            // The built-in templates are tested to parse
+            one_line: parse(language.one_line).unwrap_or_default(),
+            multi_line: parse(language.multi_line).unwrap_or_default(),
+        }
+    }
+
+    /// Uses `template` for [`one_line`](Summarizer::one_line) summaries.
+    ///
+    /// # Errors
+    /// If the template has an unknown field or unbalanced `{}` or `[]`.
+    pub fn with_one_line_template(mut self, template: &str) -> Result<Self, SummaryTemplateError> {
+        self.one_line = parse(template)?;
+        Ok(self)
+    }
+
+    /// Uses `template` for [`multi_line`](Summarizer::multi_line) summaries.
+    ///
+    /// # Errors
+    /// If the template has an unknown field or unbalanced `{}` or `[]`.
+    pub fn with_multi_line_template(mut self, template: &str) -> Result<Self, SummaryTemplateError> {
+        self.multi_line = parse(template)?;
+        Ok(self)
+    }
+
+    /// A one-line summary, e.g. `Paris, FR: light rain, 15°C (feels like 14°C), humidity 72%, wind 4.5 m/s WSW,
+    /// rain 0.4 mm (1h)`.
+    pub fn one_line(&self, reading: &CurrentWeather) -> String {
+        self.render(&self.one_line, reading)
+    }
+
+    /// A summary with one line per measurement.
+    pub fn multi_line(&self, reading: &CurrentWeather) -> String {
+        self.render(&self.multi_line, reading)
+    }
+
+    fn render(&self, pieces: &[Piece], reading: &CurrentWeather) -> String {
+        let mut summary = String::new();
+        for piece in pieces {
+            match piece {
+                Piece::Text(text) => summary.push_str(text),
+                Piece::Field(field) => summary.push_str(&self.value(*field, reading)),
+                Piece::Optional(section) => {
+                    let complete = section.iter().all(|piece| match piece {
+                        Piece::Field(field) => !self.value(*field, reading).is_empty(),
+                        _ => true,
+                    });
+                    if complete {
+                        summary.push_str(&self.render(section, reading));
+                    }
+                }
+            }
+        }
+        summary
+    }
+
+    fn value(&self, field: Field, reading: &CurrentWeather) -> String {
+        match field {
+            Field::Location => match &reading.sys.country {
+                Some(country) if !reading.name.is_empty() => format!("{}, {country}", reading.name),
+                Some(country) => country.clone(),
+                None => reading.name.clone(),
+            },
+            Field::Description => reading
+                .weather
+                .iter()
+                .map(|weather| weather.description.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            Field::Temp => self.temperature(reading.main.temp),
+            Field::FeelsLike => self.temperature(reading.main.feels_like),
+            Field::Humidity => format!("{}%", self.decimal(reading.main.humidity, 0)),
+            Field::Pressure => format!("{} hPa", self.decimal(reading.main.pressure, 0)),
+            Field::Wind => match reading.wind.deg {
+                Some(deg) => format!(
+                    "{} {}",
+                    self.speed(reading.wind.speed),
+                    self.language.compass[compass_index(deg)]
+                ),
+                None => self.speed(reading.wind.speed),
+            },
+            Field::WindSpeed => self.speed(reading.wind.speed),
+            Field::WindDirection => reading
+                .wind
+                .deg
+                .map(|deg| self.language.compass[compass_index(deg)].to_string())
+                .unwrap_or_default(),
+            Field::Gust => reading.wind.gust.map(|gust| self.speed(gust)).unwrap_or_default(),
+            Field::Clouds => reading
+                .clouds
+                .as_ref()
+                .map(|clouds| format!("{}%", self.decimal(clouds.cloudiness, 0)))
+                .unwrap_or_default(),
+            Field::Precipitation => [(self.language.rain, &reading.rain), (self.language.snow, &reading.snow)]
+                .into_iter()
+                .filter_map(|(label, volume)| Some(format!("{label} {}", self.precipitation(volume.as_ref()?)?)))
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+
+    fn temperature(&self, value: f64) -> String {
+        let value = self.decimal(value, 0);
+        match self.units {
+            UnitSystem::Metric => format!("{value}°C"),
+            UnitSystem::Imperial => format!("{value}°F"),
+            UnitSystem::Standard => format!("{value} K"),
+        }
+    }
+
+    fn speed(&self, value: f64) -> String {
+        let unit = match self.units {
+            UnitSystem::Imperial => "mph",
+            UnitSystem::Metric | UnitSystem::Standard => "m/s",
+        };
+        format!("{} {unit}", self.decimal(value, 1))
+    }
+
+    /// Precipitation is the volume in mm whatever the units, preferably over the last hour.
+    fn precipitation(&self, volume: &PrecipVolume) -> Option<String> {
+        match (volume.one_hour, volume.three_hour) {
+            (Some(mm), _) => Some(format!("{} mm (1h)", self.decimal(mm, 1))),
+            (None, Some(mm)) => Some(format!("{} mm (3h)", self.decimal(mm, 1))),
+            (None, None) => None,
+        }
+    }
+
+    /// `value` with `decimals` digits after the language's decimal separator, and without a sign if it rounds to 0.
+    fn decimal(&self, value: f64, decimals: usize) -> String {
+        let mut text = format!("{value:.decimals$}");
+        if text.chars().all(|c| matches!(c, '-' | '0' | '.')) {
+            text = text.replace('-', "");
+        }
+        if self.language.decimal_comma {
+            text = text.replace('.', ",");
+        }
+        text
+    }
+}
+
+impl Client {
+    /// A [`Summarizer`] for readings fetched by this client, in its language and units.  The labels are in English
+    /// unless the client's language is one of those built in, see the [module documentation](crate::summary); give
+    /// it templates of your own with [`Summarizer::with_one_line_template`] and
+    /// [`Summarizer::with_multi_line_template`] to label readings in other languages.
+    pub fn summarizer(&self) -> Summarizer {
+        Summarizer::new(self.language(), self.units())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PARIS;
+
+    fn paris() -> CurrentWeather {
+        serde_yaml::from_str(PARIS).unwrap()
+    }
+
+    #[test]
+    fn one_line_summaries_use_the_unit_symbols() {
+        let summary = Summarizer::new("en", UnitSystem::Imperial).one_line(&paris());
+
+        assert_eq!(
+            summary,
+            "Paris, FR: light rain, 59°F (feels like 57°F), humidity 72%, wind 10.0 mph WSW, rain 0.4 mm (1h)"
+        );
+    }
+
+    #[test]
+    fn multi_line_summaries_are_localized() {
+        let summary = Summarizer::new("fr", UnitSystem::Metric).multi_line(&paris());
+
+        assert_eq!(
+            summary,
+            "Paris, FR\nlight rain\nTempérature : 59°C (ressenti 57°C)\nHumidité : 72%\nVent : 10,0 m/s OSO\n\
+             Précipitations : pluie 0,4 mm (1h)"
+        );
+    }
+
+    #[test]
+    fn optional_sections_need_all_their_fields() {
+        let mut reading = paris();
+        reading.rain = None;
+        let summarizer = Summarizer::new("de_at", UnitSystem::Standard)
+            .with_one_line_template("{location} {{{temp}}}[ {precipitation}][ {wind_direction}]")
+            .unwrap();
+
+        assert_eq!(summarizer.one_line(&reading), "Paris, FR {59 K} WSW");
+    }
+
+    #[test]
+    fn invalid_templates_are_rejected() {
+        let message = |template| {
+            Summarizer::new("en", UnitSystem::Metric)
+                .with_one_line_template(template)
+                .unwrap_err()
+                .message
+        };
+
+        assert_eq!(message("{temperature}"), "unknown field {temperature}");
+        assert_eq!(message("{temp"), "unclosed {");
+        assert_eq!(message("temp}"), "unmatched }");
+        assert_eq!(message("[[{temp}]"), "unmatched ]");
+        assert_eq!(message("[{temp}[{gust}]]"), "[ sections can't be nested");
+        assert_eq!(message("[{temp}"), "unclosed [");
+    }
+
+    #[test]
+    fn built_in_templates_parse() {
+        for language in &LANGUAGES {
+            assert!(parse(language.one_line).is_ok(), "{:?}", language.codes);
+            assert!(parse(language.multi_line).is_ok(), "{:?}", language.codes);
+        }
+    }
+
+    #[test]
+    fn compass_points() {
+        assert_eq!(compass_point(0.0, "en"), "N");
+        assert_eq!(compass_point(350.0, "en"), "N");
+        assert_eq!(compass_point(-90.0, "fr"), "O");
+        assert_eq!(compass_point(135.0, "de"), "SO");
+        assert_eq!(compass_point(240.0, "zh_cn"), "WSW");
+        assert_eq!(compass_point(240.0, "se"), "VSV");
+        assert_eq!(compass_point(135.0, "pt_br"), "SE");
+    }
+
+    #[test]
+    fn three_hour_volumes_say_so() {
+        let mut reading = paris();
+        reading.rain = None;
+        reading.snow = Some(PrecipVolume {
+            one_hour: None,
+            three_hour: Some(1.5),
+            ..PrecipVolume::default()
+        });
+        let summarizer = Summarizer::new("it", UnitSystem::Metric)
+            .with_one_line_template("{precipitation}")
+            .unwrap();
+
+        assert_eq!(summarizer.one_line(&reading), "neve 1,5 mm (3h)");
+    }
+}
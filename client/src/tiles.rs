@@ -0,0 +1,485 @@
+//! Weather map tiles from [Weather Maps 1.0](https://openweathermap.org/api/weathermaps) and
+//! [Weather Maps 2.0](https://openweathermap.org/api/weather-map-2) (paid subscription).  Tiles are 256x256 PNG
+//! images addressed with the usual `z/x/y` slippy map scheme.
+
+use hyper::{body::Bytes, Method};
+
+use crate::{
+    client::ApiRequest,
+    error::{ApiCallError, TileError},
+    hosts::ApiHost,
+    models::{BoundingBox, Coord},
+    query::QueryParameters,
+    Client,
+};
+
+/// Api docs are here <https://openweathermap.org/api/weathermaps>
+const MAPS_V1_PATH: &str = "/map";
+
+/// Api docs are here <https://openweathermap.org/api/weather-map-2>
+const MAPS_V2_PATH: &str = "/maps/2.0/weather";
+
+/// Most tiles a box may be covered with, so a high zoom level doesn't fetch a country one tile at a time.
+pub const MAX_TILES: usize = 256;
+
+/// Deepest zoom level, where `x` and `y` still fit in a `u32`.
+const MAX_ZOOM: u8 = 31;
+
+/// Web mercator can't represent the poles; tiles stop at this latitude.
+const MAX_MERCATOR_LATITUDE: f64 = 85.051_128_78;
+
+/// Layers of Weather Maps 1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClassicLayer {
+    /// `clouds_new`
+    Clouds,
+    /// `precipitation_new`
+    Precipitation,
+    /// `pressure_new`
+    Pressure,
+    /// `wind_new`
+    Wind,
+    /// `temp_new`
+    Temperature,
+}
+
+impl ClassicLayer {
+    /// The layer's name in the tile URL.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClassicLayer::Clouds => "clouds_new",
+            ClassicLayer::Precipitation => "precipitation_new",
+            ClassicLayer::Pressure => "pressure_new",
+            ClassicLayer::Wind => "wind_new",
+            ClassicLayer::Temperature => "temp_new",
+        }
+    }
+}
+
+/// Layers of Weather Maps 2.0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Maps2Layer {
+    /// `PAC0` Convective precipitation, mm
+    ConvectivePrecipitation,
+    /// `PR0` Precipitation intensity, mm/s
+    PrecipitationIntensity,
+    /// `PA0` Accumulated precipitation, mm
+    AccumulatedPrecipitation,
+    /// `PAR0` Accumulated precipitation - rain, mm
+    AccumulatedRain,
+    /// `PAS0` Accumulated precipitation - snow, mm
+    AccumulatedSnow,
+    /// `SD0` Depth of snow, m
+    SnowDepth,
+    /// `WS10` Wind speed at an altitude of 10 meters, m/s
+    WindSpeed,
+    /// `WND` Joint display of speed wind (color) and wind direction (arrows), m/s
+    Wind,
+    /// `APM` Atmospheric pressure on mean sea level, hPa
+    Pressure,
+    /// `TA2` Air temperature at a height of 2 meters, °C
+    Temperature,
+    /// `TD2` Temperature of a dew point, °C
+    DewPoint,
+    /// `TS0` Soil temperature 0-10 сm, K
+    SoilTemperatureShallow,
+    /// `TS10` Soil temperature >10 сm, K
+    SoilTemperatureDeep,
+    /// `HRD0` Relative humidity, %
+    Humidity,
+    /// `CL` Cloudiness, %
+    Clouds,
+}
+
+impl Maps2Layer {
+    /// The layer's operation code in the tile URL.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Maps2Layer::ConvectivePrecipitation => "PAC0",
+            Maps2Layer::PrecipitationIntensity => "PR0",
+            Maps2Layer::AccumulatedPrecipitation => "PA0",
+            Maps2Layer::AccumulatedRain => "PAR0",
+            Maps2Layer::AccumulatedSnow => "PAS0",
+            Maps2Layer::SnowDepth => "SD0",
+            Maps2Layer::WindSpeed => "WS10",
+            Maps2Layer::Wind => "WND",
+            Maps2Layer::Pressure => "APM",
+            Maps2Layer::Temperature => "TA2",
+            Maps2Layer::DewPoint => "TD2",
+            Maps2Layer::SoilTemperatureShallow => "TS0",
+            Maps2Layer::SoilTemperatureDeep => "TS10",
+            Maps2Layer::Humidity => "HRD0",
+            Maps2Layer::Clouds => "CL",
+        }
+    }
+}
+
+/// Optional parameters of a Weather Maps 2.0 tile.  Anything left `None` uses the API's default.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Maps2Options {
+    /// Time of the map, unix, UTC (in seconds).  Defaults to now.
+    pub date: Option<i64>,
+    /// Opacity of the layer, 0 to 1.
+    pub opacity: Option<f64>,
+    /// Custom color palette, `{value}:{HEX color};...` e.g. `0:FF0000;10:00FF00;20:0000FF`.
+    pub palette: Option<String>,
+    /// Whether values outside the palette's range are filled with its boundary colors.
+    pub fill_bound: Option<bool>,
+    /// Step of the wind arrows in pixels, for [`Maps2Layer::Wind`].
+    pub arrow_step: Option<u32>,
+    /// Whether wind arrows are normalized, for [`Maps2Layer::Wind`].
+    pub use_norm: Option<bool>,
+}
+
+impl Maps2Options {
+    fn query_params(&self) -> QueryParameters {
+        let mut params = Vec::new();
+        if let Some(date) = self.date {
+            params.push(("date", date.to_string()));
+        }
+        if let Some(opacity) = self.opacity {
+            params.push(("opacity", opacity.to_string()));
+        }
+        if let Some(palette) = &self.palette {
+            params.push(("palette", palette.clone()));
+        }
+        if let Some(fill_bound) = self.fill_bound {
+            params.push(("fill_bound", fill_bound.to_string()));
+        }
+        if let Some(arrow_step) = self.arrow_step {
+            params.push(("arrow_step", arrow_step.to_string()));
+        }
+        if let Some(use_norm) = self.use_norm {
+            params.push(("use_norm", use_norm.to_string()));
+        }
+        params
+    }
+}
+
+/// A map layer from either version of the Weather Maps API.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TileLayer {
+    /// See [`ClassicLayer`]
+    Classic(ClassicLayer),
+    /// See [`Maps2Layer`] and [`Maps2Options`]
+    Maps2 {
+        /// The layer
+        layer: Maps2Layer,
+        /// Its options
+        options: Maps2Options,
+    },
+}
+
+impl From<ClassicLayer> for TileLayer {
+    fn from(layer: ClassicLayer) -> Self {
+        TileLayer::Classic(layer)
+    }
+}
+
+impl From<Maps2Layer> for TileLayer {
+    fn from(layer: Maps2Layer) -> Self {
+        TileLayer::Maps2 {
+            layer,
+            options: Maps2Options::default(),
+        }
+    }
+}
+
+impl TileLayer {
+    fn request(&self, tile: TileCoord) -> ApiRequest {
+        let TileCoord { z, x, y } = tile;
+        let (host, path, params) = match self {
+            TileLayer::Classic(layer) => (
+                ApiHost::Tile,
+                format!("{MAPS_V1_PATH}/{}/{z}/{x}/{y}.png", layer.name()),
+                Vec::new(),
+            ),
+            TileLayer::Maps2 { layer, options } => (
+                ApiHost::Maps,
+                format!("{MAPS_V2_PATH}/{}/{z}/{x}/{y}", layer.code()),
+                options.query_params(),
+            ),
+        };
+        ApiRequest {
+            endpoint: "tile",
+            method: Method::GET,
+            host,
+            path,
+            subject: format!("{z}/{x}/{y}"),
+            params,
+            body: None,
+        }
+    }
+}
+
+/// Address of a tile in the slippy map scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileCoord {
+    /// Zoom level, 0 is the whole world in one tile
+    pub z: u8,
+    /// Column, 0 at 180°W
+    pub x: u32,
+    /// Row, 0 at the northern edge
+    pub y: u32,
+}
+
+impl TileCoord {
+    /// The tile at zoom level `z` (at most 31) containing `coord`.  Latitudes beyond ±85.05° fall in the top or
+    /// bottom row.
+    pub fn containing(coord: &Coord, z: u8) -> TileCoord {
+        let z = z.min(MAX_ZOOM);
+        let tiles_per_side = f64::from(1_u32 << z);
+        let max_index = (1_u32 << z) - 1;
+
+        let lon = coord.lon.clamp(-180.0, 180.0);
+        let lat = coord
+            .lat
+            .clamp(-MAX_MERCATOR_LATITUDE, MAX_MERCATOR_LATITUDE)
+            .to_radians();
+
+        let x = ((lon + 180.0) / 360.0 * tiles_per_side).floor();
+        let y = ((1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / std::f64::consts::PI) / 2.0 * tiles_per_side).floor();
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // both are clamped to 0..2^z
+        TileCoord {
+            z,
+            x: (x.max(0.0) as u32).min(max_index),
+            y: (y.max(0.0) as u32).min(max_index),
+        }
+    }
+
+    /// Every tile at zoom level `z` that overlaps `bbox`, row by row from the north-west corner.  A box crossing the
+    /// antimeridian (`west > east`) wraps around.
+    ///
+    /// # Errors
+    /// If more than [`MAX_TILES`] tiles overlap `bbox`.
+    pub fn covering(bbox: &BoundingBox, z: u8) -> Result<Vec<TileCoord>, TileError> {
+        let north_west = TileCoord::containing(&Coord::new(bbox.north, bbox.west), z);
+        let south_east = TileCoord::containing(&Coord::new(bbox.south, bbox.east), z);
+
+        let column_count = if north_west.x <= south_east.x {
+            u64::from(south_east.x - north_west.x) + 1
+        } else {
+            (1_u64 << north_west.z) - u64::from(north_west.x) + u64::from(south_east.x) + 1
+        };
+        let row_count = u64::from(south_east.y.saturating_sub(north_west.y)) + 1;
+        let count = column_count.saturating_mul(row_count);
+        if usize::try_from(count).map_or(true, |count| count > MAX_TILES) {
+            return Err(TileError {
+                message: format!("{count} tiles at zoom level {z} overlap {bbox:?}, more than {MAX_TILES}"),
+            });
+        }
+
+        let columns: Vec<u32> = if north_west.x <= south_east.x {
+            (north_west.x..=south_east.x).collect()
+        } else {
+            (north_west.x..(1_u32 << north_west.z))
+                .chain(0..=south_east.x)
+                .collect()
+        };
+
+        Ok((north_west.y..=south_east.y)
+            .flat_map(|y| columns.iter().map(move |&x| TileCoord { z: north_west.z, x, y }))
+            .collect())
+    }
+
+    /// Checks the tile exists: `z` is at most 31, and `x` and `y` are below `2^z`.
+    ///
+    /// # Errors
+    /// If the tile is outside the map.
+    pub fn validate(&self) -> Result<(), TileError> {
+        if self.z > MAX_ZOOM {
+            return Err(TileError {
+                message: format!("zoom level {} is deeper than {MAX_ZOOM}", self.z),
+            });
+        }
+        let tiles_per_side = 1_u64 << self.z;
+        if u64::from(self.x) >= tiles_per_side || u64::from(self.y) >= tiles_per_side {
+            return Err(TileError {
+                message: format!(
+                    "{}/{}/{} is outside the map, x and y must be below {tiles_per_side}",
+                    self.z, self.x, self.y
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A map tile image.
+#[derive(Debug, Clone)]
+pub struct Tile {
+    /// Where the tile is
+    pub coord: TileCoord,
+    /// The response's `Content-Type`, normally `image/png`
+    pub content_type: Option<String>,
+    /// The image
+    pub bytes: Bytes,
+}
+
+impl Client {
+    /// Fetch the tile at `z/x/y` of a map layer.
+    ///
+    /// # Errors
+    /// [`ApiCallError::InvalidTile`] without calling the API if the tile is outside the map, see
+    /// [`TileCoord::validate`].  May fail for a variety of other reasons, See [`ApiCallError`].
+    pub async fn fetch_tile(&self, layer: &TileLayer, z: u8, x: u32, y: u32) -> Result<Tile, ApiCallError> {
+        let coord = TileCoord { z, x, y };
+        coord.validate()?;
+        let response = self.call(&layer.request(coord)).await?;
+        let content_type = response
+            .response
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string);
+
+        Ok(Tile {
+            coord,
+            content_type,
+            bytes: response.response.into_body(),
+        })
+    }
+
+    /// Fetch, one after another, every tile at zoom level `z` that overlaps `bbox`.  See [`TileCoord::covering`].
+    /// A failed tile doesn't stop the others from being fetched.
+    ///
+    /// # Errors
+    /// Without calling the API if more than [`MAX_TILES`] tiles overlap `bbox`.
+    pub async fn fetch_tiles_covering(
+        &self,
+        layer: &TileLayer,
+        bbox: &BoundingBox,
+        z: u8,
+    ) -> Result<Vec<(TileCoord, Result<Tile, ApiCallError>)>, TileError> {
+        let mut tiles = Vec::new();
+        for coord in TileCoord::covering(bbox, z)? {
+            let tile = self.fetch_tile(layer, coord.z, coord.x, coord.y).await;
+            tiles.push((coord, tile));
+        }
+        Ok(tiles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn containing_tile() {
+        assert_eq!(
+            TileCoord::containing(&Coord::new(0.0, 0.0), 0),
+            TileCoord { z: 0, x: 0, y: 0 }
+        );
+        // Paris
+        assert_eq!(
+            TileCoord::containing(&Coord::new(48.8566, 2.3522), 10),
+            TileCoord { z: 10, x: 518, y: 352 }
+        );
+        // Sydney
+        assert_eq!(
+            TileCoord::containing(&Coord::new(-33.8688, 151.2093), 8),
+            TileCoord { z: 8, x: 235, y: 153 }
+        );
+    }
+
+    #[test]
+    fn containing_tile_clamps_to_the_edges() {
+        assert_eq!(
+            TileCoord::containing(&Coord::new(90.0, 180.0), 2),
+            TileCoord { z: 2, x: 3, y: 0 }
+        );
+        assert_eq!(
+            TileCoord::containing(&Coord::new(-90.0, -180.0), 2),
+            TileCoord { z: 2, x: 0, y: 3 }
+        );
+    }
+
+    #[test]
+    fn covering_tiles() {
+        let bbox = BoundingBox::new(48.0, 2.0, 49.0, 3.0);
+        let tiles = TileCoord::covering(&bbox, 8).unwrap();
+        assert_eq!(
+            tiles,
+            vec![
+                TileCoord { z: 8, x: 129, y: 87 },
+                TileCoord { z: 8, x: 130, y: 87 },
+                TileCoord { z: 8, x: 129, y: 88 },
+                TileCoord { z: 8, x: 130, y: 88 },
+            ]
+        );
+    }
+
+    #[test]
+    fn covering_tiles_across_the_antimeridian() {
+        let bbox = BoundingBox::new(-10.0, 170.0, 10.0, -170.0);
+        let columns: Vec<u32> = TileCoord::covering(&bbox, 2).unwrap().iter().map(|t| t.x).collect();
+        assert_eq!(columns, vec![3, 0, 3, 0]);
+    }
+
+    #[test]
+    fn covering_too_many_tiles_is_an_error() {
+        let france = BoundingBox::new(42.0, -5.0, 51.0, 8.0);
+        assert!(TileCoord::covering(&france, 6).is_ok());
+        let error = TileCoord::covering(&france, 12).unwrap_err();
+        assert!(error.message.contains("more than 256"), "{error}");
+        assert!(TileCoord::covering(&BoundingBox::new(-80.0, -170.0, 80.0, 170.0), 31).is_err());
+    }
+
+    #[test]
+    fn tiles_outside_the_map_are_invalid() {
+        assert!(TileCoord { z: 0, x: 0, y: 0 }.validate().is_ok());
+        assert!(TileCoord {
+            z: 31,
+            x: (1 << 31) - 1,
+            y: 0
+        }
+        .validate()
+        .is_ok());
+        for tile in [
+            TileCoord { z: 32, x: 0, y: 0 },
+            TileCoord { z: 2, x: 4, y: 0 },
+            TileCoord { z: 2, x: 0, y: 4 },
+            TileCoord {
+                z: 31,
+                x: 1 << 31,
+                y: 0,
+            },
+        ] {
+            assert!(tile.validate().is_err(), "{tile:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn invalid_tiles_are_not_fetched() {
+        let client = Client::builder().api_key("TEST_KEY").build().unwrap();
+        let result = client.fetch_tile(&ClassicLayer::Clouds.into(), 3, 8, 0).await;
+        assert!(matches!(result, Err(ApiCallError::InvalidTile(_))), "{result:?}");
+    }
+
+    #[test]
+    fn tile_urls() {
+        let tile = TileCoord { z: 3, x: 4, y: 2 };
+
+        let request = TileLayer::from(ClassicLayer::Precipitation).request(tile);
+        assert_eq!(request.host, ApiHost::Tile);
+        assert_eq!(request.path, "/map/precipitation_new/3/4/2.png");
+        assert!(request.params.is_empty());
+
+        let request = TileLayer::Maps2 {
+            layer: Maps2Layer::Temperature,
+            options: Maps2Options {
+                date: Some(1_552_861_800),
+                opacity: Some(0.6),
+                ..Maps2Options::default()
+            },
+        }
+        .request(tile);
+        assert_eq!(request.host, ApiHost::Maps);
+        assert_eq!(request.path, "/maps/2.0/weather/TA2/3/4/2");
+        assert_eq!(
+            request.params,
+            vec![("date", "1552861800".to_string()), ("opacity", "0.6".to_string())]
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! The HTTP layer under the [`Client`](crate::Client).  [`HyperTransport`] is used unless another [`Transport`] is
+//! supplied with [`Client::with_transport`](crate::Client::with_transport), e.g. an instrumented stack, a transport
+//! sharing an existing connection pool or an in-process mock for tests.
+
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Bytes, Request, Response, Uri};
+use hyper_rustls::HttpsConnector;
+use hyper_util::{
+    client::legacy::{
+        connect::{proxy::Tunnel, HttpConnector},
+        Client as HyperClient,
+    },
+    rt::TokioExecutor,
+};
+use rustls::{
+    pki_types::{pem::PemObject, CertificateDer},
+    ClientConfig, RootCertStore,
+};
+use std::{future::Future, path::PathBuf, pin::Pin, time::Duration};
+
+use crate::error::{ClientError, InvalidOptionsError};
+
+/// The error of a failed [`Transport::send`].
+pub type TransportError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The future returned by [`Transport::send`].
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<Response<Bytes>, TransportError>> + Send + 'a>>;
+
+/// Sends one request and reads the whole response.  Any response, whatever its status, is an `Ok`; an `Err` means
+/// no response was obtained.
+pub trait Transport: Send + Sync {
+    /// Send `request`, whose URI includes the api key.
+    fn send(&self, request: Request<Bytes>) -> TransportFuture<'_>;
+}
+
+/// The hyper client the [`HyperTransport`] sends requests with.
+pub type HttpClient = HyperClient<HttpsConnector<HttpConnector>, Full<Bytes>>;
+
+type ProxiedHttpClient = HyperClient<HttpsConnector<Tunnel<HttpConnector>>, Full<Bytes>>;
+
+/// A [`Transport`] over a hyper client with rustls and the native root certificates.
+#[derive(Clone)]
+pub struct HyperTransport {
+    http: Http,
+}
+
+#[derive(Clone)]
+enum Http {
+    Direct(HttpClient),
+    Proxied(ProxiedHttpClient),
+}
+
+/// Connection settings of a [`HyperTransport`] built by a [`ClientBuilder`](crate::ClientBuilder).
+#[derive(Debug, Default)]
+pub(crate) struct HttpSettings {
+    pub(crate) connect_timeout: Option<Duration>,
+    /// PEM files of root certificates trusted in addition to the native ones.
+    pub(crate) root_certificates: Vec<PathBuf>,
+    /// Also allow plain `http` connections.
+    pub(crate) allow_insecure_http: bool,
+    /// An HTTP proxy every connection is tunnelled through with `CONNECT`.
+    pub(crate) proxy: Option<Uri>,
+}
+
+impl HyperTransport {
+    /// A transport with its own connection pool, refusing plain `http`.
+    ///
+    /// # Errors
+    /// If the native root certificates can't be loaded.
+    pub fn new() -> Result<HyperTransport, std::io::Error> {
+        let https = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_native_roots()?
+            .https_only()
+            .enable_http1()
+            .build();
+
+        Ok(HyperTransport {
+            http: Http::Direct(HyperClient::builder(TokioExecutor::new()).build(https)),
+        })
+    }
+
+    /// A transport sharing the connection pool of an existing hyper client.
+    pub fn from_client(http: HttpClient) -> HyperTransport {
+        HyperTransport {
+            http: Http::Direct(http),
+        }
+    }
+
+    /// A transport with its own connection pool, connecting as described by `settings`.
+    ///
+    /// # Errors
+    /// If the root certificates can't be loaded.
+    pub(crate) fn with_settings(settings: &HttpSettings) -> Result<HyperTransport, ClientError> {
+        let https = hyper_rustls::HttpsConnectorBuilder::new().with_tls_config(tls_config(settings)?);
+        let https = if settings.allow_insecure_http {
+            https.https_or_http()
+        } else {
+            https.https_only()
+        }
+        .enable_http1();
+
+        let mut connector = HttpConnector::new();
+        connector.enforce_http(false);
+        connector.set_connect_timeout(settings.connect_timeout);
+
+        let builder = HyperClient::builder(TokioExecutor::new());
+        let http = match &settings.proxy {
+            Some(proxy) => Http::Proxied(builder.build(https.wrap_connector(Tunnel::new(proxy.clone(), connector)))),
+            None => Http::Direct(builder.build(https.wrap_connector(connector))),
+        };
+        Ok(HyperTransport { http })
+    }
+}
+
+/// The native root certificates plus those of `settings`.
+fn tls_config(settings: &HttpSettings) -> Result<ClientConfig, ClientError> {
+    let mut roots = RootCertStore::empty();
+    let native = rustls_native_certs::load_native_certs();
+    roots.add_parsable_certificates(native.certs);
+
+    for path in &settings.root_certificates {
+        let certificates = CertificateDer::pem_file_iter(path)
+            .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+            .map_err(|e| InvalidOptionsError {
+                message: format!("root certificate {} can't be read: {e}", path.display()),
+            })?;
+        if certificates.is_empty() {
+            return Err(InvalidOptionsError {
+                message: format!("root certificate {} has no PEM certificates", path.display()),
+            }
+            .into());
+        }
+        for certificate in certificates {
+            roots.add(certificate).map_err(|e| InvalidOptionsError {
+                message: format!("root certificate {} is invalid: {e}", path.display()),
+            })?;
+        }
+    }
+
+    if roots.is_empty() {
+        return Err(InvalidOptionsError {
+            message: "no root certificates could be loaded".to_string(),
+        }
+        .into());
+    }
+    Ok(ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth())
+}
+
+impl Transport for HyperTransport {
+    fn send(&self, request: Request<Bytes>) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let request = request.map(Full::new);
+            let response = match &self.http {
+                Http::Direct(http) => http.request(request).await?,
+                Http::Proxied(http) => http.request(request).await?,
+            };
+            let (parts, body) = response.into_parts();
+            let body = body.collect().await?.to_bytes();
+            Ok(Response::from_parts(parts, body))
+        })
+    }
+}
@@ -0,0 +1,88 @@
+//! Current, forecast and historical [UV index](https://openweathermap.org/api/uvi) at a [`Coord`].
+
+use serde::Deserialize;
+
+use crate::{
+    error::ApiCallError,
+    hosts::{ApiHost, Endpoint},
+    models::Coord,
+    Client, RawResponse,
+};
+
+/// Api docs are here <https://openweathermap.org/api/uvi>
+const V25_UVI: Endpoint = Endpoint::new("uvi", ApiHost::Api, "/data/2.5/uvi");
+const V25_UVI_FORECAST: Endpoint = Endpoint::new("uvi_forecast", ApiHost::Api, "/data/2.5/uvi/forecast");
+const V25_UVI_HISTORY: Endpoint = Endpoint::new("uvi_history", ApiHost::Api, "/data/2.5/uvi/history");
+
+/// UV index at a location and time.
+#[derive(Debug, Deserialize)]
+pub struct UvIndex {
+    /// Geo location, latitude
+    pub lat: f64,
+    /// Geo location, longitude
+    pub lon: f64,
+    /// Time of the reading, ISO 8601, UTC
+    pub date_iso: Option<String>,
+    /// Time of the reading, unix, UTC (in seconds)
+    pub date: i64,
+    /// The UV index
+    pub value: f64,
+}
+
+impl Client {
+    /// Fetch the current UV index.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_uv_index(&self, coord: &Coord) -> Result<UvIndex, ApiCallError> {
+        self.get(V25_UVI, coord, Vec::new()).await
+    }
+
+    /// Fetch the current UV index without parsing it.
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_uv_index_raw(&self, coord: &Coord) -> Result<RawResponse, ApiCallError> {
+        self.get_raw(V25_UVI, coord, Vec::new()).await
+    }
+
+    /// Fetch the daily UV index forecast for the next `count` days (at most 8).
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_uv_index_forecast(&self, coord: &Coord, count: u8) -> Result<Vec<UvIndex>, ApiCallError> {
+        let params = vec![("cnt", count.to_string())];
+        self.get(V25_UVI_FORECAST, coord, params).await
+    }
+
+    /// Fetch the daily UV index between `start` and `end` (unix, UTC, in seconds).
+    ///
+    /// # Errors
+    /// May fail for a variety of reasons, See [`ApiCallError`].
+    pub async fn fetch_uv_index_history(
+        &self,
+        coord: &Coord,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<UvIndex>, ApiCallError> {
+        let params = vec![("start", start.to_string()), ("end", end.to_string())];
+        self.get(V25_UVI_HISTORY, coord, params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_response() {
+        let parsed: Vec<UvIndex> = serde_yaml::from_str(
+            r#"[{"lat":37.75,"lon":-122.37,"date_iso":"2017-06-23T12:00:00Z","date":1498219200,"value":10.16}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(parsed[0].date, 1_498_219_200);
+        assert_eq!(parsed[0].date_iso.as_deref(), Some("2017-06-23T12:00:00Z"));
+        assert!((parsed[0].value - 10.16).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,118 @@
+//! Polling the current weather as a stream of new readings.  See [`Client::watch`].
+
+use futures_util::{stream, Stream};
+use log::debug;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{error::ApiCallError, models::CurrentWeather, Client, Query};
+
+/// About how often the API updates the current weather of a location.
+pub const UPDATE_CADENCE: Duration = Duration::from_secs(600);
+
+/// Consecutive errors stretch the wait before the next poll up to this many intervals.
+const MAX_ERROR_BACKOFF_FACTOR: u32 = 16;
+
+struct WatchState<Q> {
+    client: Client,
+    query: Q,
+    interval: Duration,
+    wait: Duration,
+    last_dt: Option<i64>,
+    consecutive_errors: u32,
+}
+
+impl Client {
+    /// Polls the current weather for `query` every `interval`, yielding each reading whose `dt` differs from the
+    /// previous one.  The first poll is immediate.
+    ///
+    /// After a new reading, the next poll waits for the reading to be [`UPDATE_CADENCE`] old if that is later than
+    /// `interval`, as an earlier poll would only get the same reading again.  Errors are yielded too and back off
+    /// the polling, doubling the wait with each consecutive error up to 16 intervals.  The stream never ends; drop it
+    /// to stop polling.
+    pub fn watch<Q: Query + 'static>(
+        &self,
+        query: Q,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<CurrentWeather, ApiCallError>> + Send + 'static {
+        let state = WatchState {
+            client: self.clone(),
+            query,
+            interval,
+            wait: Duration::ZERO,
+            last_dt: None,
+            consecutive_errors: 0,
+        };
+        stream::unfold(state, |mut state| async move {
+            loop {
+                tokio::time::sleep(state.wait).await;
+                match state.client.fetch_weather(&state.query).await {
+                    Ok(reading) => {
+                        state.consecutive_errors = 0;
+                        state.wait = wait_after_reading(reading.dt, unix_now(), state.interval);
+                        if state.last_dt == Some(reading.dt) {
+                            debug!("No new reading for {} since dt={}", state.query, reading.dt);
+                            continue;
+                        }
+                        state.last_dt = Some(reading.dt);
+                        return Some((Ok(reading), state));
+                    }
+                    Err(e) => {
+                        state.consecutive_errors += 1;
+                        state.wait = wait_after_errors(state.interval, state.consecutive_errors);
+                        return Some((Err(e), state));
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// The wait before polling again after a reading taken at `dt`, at unix time `now`.
+fn wait_after_reading(dt: i64, now: i64, interval: Duration) -> Duration {
+    let next_update = dt.saturating_add(i64::try_from(UPDATE_CADENCE.as_secs()).unwrap_or(i64::MAX));
+    let until_update = u64::try_from(next_update.saturating_sub(now)).unwrap_or(0);
+    interval.max(Duration::from_secs(until_update))
+}
+
+/// The wait before polling again after `consecutive_errors` errors in a row.
+fn wait_after_errors(interval: Duration, consecutive_errors: u32) -> Duration {
+    let factor = 2_u32
+        .saturating_pow(consecutive_errors.saturating_sub(1))
+        .min(MAX_ERROR_BACKOFF_FACTOR);
+    interval.saturating_mul(factor)
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polls_wait_for_the_next_update_of_a_fresh_reading() {
+        let minute = Duration::from_secs(60);
+
+        assert_eq!(wait_after_reading(1000, 1100, minute), Duration::from_secs(500));
+        assert_eq!(wait_after_reading(1000, 1580, minute), minute);
+        assert_eq!(wait_after_reading(1000, 5000, minute), minute);
+        assert_eq!(
+            wait_after_reading(1000, 1100, Duration::from_secs(3600)),
+            Duration::from_secs(3600)
+        );
+    }
+
+    #[test]
+    fn errors_back_off_up_to_16_intervals() {
+        let second = Duration::from_secs(1);
+
+        let waits: Vec<u64> = (1..=7)
+            .map(|errors| wait_after_errors(second, errors).as_secs())
+            .collect();
+
+        assert_eq!(waits, [1, 2, 4, 8, 16, 16, 16]);
+    }
+}
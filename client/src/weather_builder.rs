@@ -0,0 +1,262 @@
+use crate::models::{Clouds, Coord, CurrentWeather, Extra, Main, PrecipVolume, Sys, Weather, Wind};
+
+/// Default time of the reading, noon UTC on 2023-11-15.
+const DEFAULT_DT: i64 = 1_700_049_600;
+/// Paris' sunrise and sunset that day, relative to [`DEFAULT_DT`].
+const DEFAULT_SUNRISE: i64 = -5 * 3600 - 5 * 60;
+const DEFAULT_SUNSET: i64 = 3 * 3600 + 4 * 60;
+
+/// Builds a [`CurrentWeather`] for tests, without a JSON fixture.  Obtained from [`CurrentWeather::builder`].
+///
+/// By default the reading is of clear skies in Paris, in metric units: 15°C, 60% humidity, 1013 hPa and calm wind.
+/// Set only what the test is about:
+///
+/// ```
+/// use openweathermap_client::models::{CurrentWeather, Weather};
+///
+/// let reading = CurrentWeather::builder()
+///     .location("Oslo", "NO")
+///     .temp(-7.5)
+///     .wind(12.0, 20.0)
+///     .weather(Weather::new(601, "Snow", "snow", "13d"))
+///     .snow_1h(1.2)
+///     .build();
+///
+/// assert_eq!(reading.main.feels_like, -7.5);
+/// assert_eq!(reading.sys.country.as_deref(), Some("NO"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CurrentWeatherBuilder {
+    reading: CurrentWeather,
+    feels_like: Option<f64>,
+    temp_range: Option<(f64, f64)>,
+    weather: Vec<Weather>,
+}
+
+impl Default for CurrentWeatherBuilder {
+    fn default() -> Self {
+        CurrentWeatherBuilder {
+            reading: CurrentWeather {
+                coord: Coord::new(48.8534, 2.3488),
+                weather: Vec::new(),
+                base: Some("stations".to_string()),
+                main: Main::default(),
+                visibility: Some(10_000),
+                wind: Wind::default(),
+                clouds: Some(Clouds::default()),
+                rain: None,
+                snow: None,
+                dt: DEFAULT_DT,
+                sys: Sys {
+                    country: Some("FR".to_string()),
+                    sunrise: Some(DEFAULT_DT + DEFAULT_SUNRISE),
+                    sunset: Some(DEFAULT_DT + DEFAULT_SUNSET),
+                    ..Sys::default()
+                },
+                timezone: 3600,
+                id: 2_988_507,
+                name: "Paris".to_string(),
+                cod: Some(200),
+                extra: Extra::new(),
+            },
+            feels_like: None,
+            temp_range: None,
+            weather: Vec::new(),
+        }
+    }
+}
+
+impl CurrentWeatherBuilder {
+    /// The city name and country code.
+    #[must_use]
+    pub fn location(mut self, name: impl Into<String>, country: impl Into<String>) -> Self {
+        self.reading.name = name.into();
+        self.reading.sys.country = Some(country.into());
+        self
+    }
+
+    /// The city id, 0 away from any city.
+    #[must_use]
+    pub fn city_id(mut self, id: u64) -> Self {
+        self.reading.id = id;
+        self
+    }
+
+    /// Where the reading was made.
+    #[must_use]
+    pub fn coord(mut self, lat: f64, lon: f64) -> Self {
+        self.reading.coord = Coord::new(lat, lon);
+        self
+    }
+
+    /// When the reading was made, unix, UTC.  Sunrise and sunset move along with it.
+    #[must_use]
+    pub fn dt(mut self, dt: i64) -> Self {
+        let shift = dt - self.reading.dt;
+        self.reading.dt = dt;
+        self.reading.sys.sunrise = self.reading.sys.sunrise.map(|sunrise| sunrise + shift);
+        self.reading.sys.sunset = self.reading.sys.sunset.map(|sunset| sunset + shift);
+        self
+    }
+
+    /// Shift in seconds from UTC.
+    #[must_use]
+    pub fn timezone(mut self, timezone: i64) -> Self {
+        self.reading.timezone = timezone;
+        self
+    }
+
+    /// Sunrise and sunset, unix, UTC, or `None` during polar day and night.
+    #[must_use]
+    pub fn sun(mut self, sunrise_and_sunset: Option<(i64, i64)>) -> Self {
+        self.reading.sys.sunrise = sunrise_and_sunset.map(|(sunrise, _)| sunrise);
+        self.reading.sys.sunset = sunrise_and_sunset.map(|(_, sunset)| sunset);
+        self
+    }
+
+    /// Adds a weather condition, replacing the default clear sky.
+    #[must_use]
+    pub fn weather(mut self, weather: Weather) -> Self {
+        self.weather.push(weather);
+        self
+    }
+
+    /// The temperature.  Unless set, the felt, minimum and maximum temperatures are the same.
+    #[must_use]
+    pub fn temp(mut self, temp: f64) -> Self {
+        self.reading.main.temp = temp;
+        self
+    }
+
+    /// The felt temperature.
+    #[must_use]
+    pub fn feels_like(mut self, feels_like: f64) -> Self {
+        self.feels_like = Some(feels_like);
+        self
+    }
+
+    /// The minimum and maximum temperatures currently observed.
+    #[must_use]
+    pub fn temp_range(mut self, min: f64, max: f64) -> Self {
+        self.temp_range = Some((min, max));
+        self
+    }
+
+    /// Atmospheric pressure, hPa.
+    #[must_use]
+    pub fn pressure(mut self, pressure: f64) -> Self {
+        self.reading.main.pressure = pressure;
+        self
+    }
+
+    /// Humidity, %.
+    #[must_use]
+    pub fn humidity(mut self, humidity: f64) -> Self {
+        self.reading.main.humidity = humidity;
+        self
+    }
+
+    /// Wind speed and direction in degrees.
+    #[must_use]
+    pub fn wind(mut self, speed: f64, deg: f64) -> Self {
+        self.reading.wind.speed = speed;
+        self.reading.wind.deg = Some(deg);
+        self
+    }
+
+    /// Wind gust.
+    #[must_use]
+    pub fn gust(mut self, gust: f64) -> Self {
+        self.reading.wind.gust = Some(gust);
+        self
+    }
+
+    /// Cloudiness, %, or `None` to leave it out like the API sometimes does.
+    #[must_use]
+    pub fn clouds(mut self, cloudiness: Option<f64>) -> Self {
+        self.reading.clouds = cloudiness.map(|cloudiness| Clouds {
+            cloudiness,
+            extra: Extra::new(),
+        });
+        self
+    }
+
+    /// Rain volume for the last hour, mm.
+    #[must_use]
+    pub fn rain_1h(mut self, mm: f64) -> Self {
+        self.reading.rain.get_or_insert_with(PrecipVolume::default).one_hour = Some(mm);
+        self
+    }
+
+    /// Snow volume for the last hour, mm.
+    #[must_use]
+    pub fn snow_1h(mut self, mm: f64) -> Self {
+        self.reading.snow.get_or_insert_with(PrecipVolume::default).one_hour = Some(mm);
+        self
+    }
+
+    /// Visibility, meter, or `None` to leave it out.
+    #[must_use]
+    pub fn visibility(mut self, visibility: Option<u16>) -> Self {
+        self.reading.visibility = visibility;
+        self
+    }
+
+    /// Adds a field the models don't know about, see [`Extra`].
+    #[must_use]
+    pub fn extra(mut self, name: impl Into<String>, value: serde_json::Value) -> Self {
+        self.reading.extra.insert(name.into(), value);
+        self
+    }
+
+    /// Creates the reading.
+    pub fn build(self) -> CurrentWeather {
+        let mut reading = self.reading;
+        let temp = reading.main.temp;
+        reading.main.feels_like = self.feels_like.unwrap_or(temp);
+        (reading.main.temp_min, reading.main.temp_max) = self.temp_range.unwrap_or((temp, temp));
+        reading.weather = if self.weather.is_empty() {
+            vec![Weather::default()]
+        } else {
+            self.weather
+        };
+        reading
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_a_mild_clear_day() {
+        let reading = CurrentWeather::builder().build();
+
+        assert_eq!(reading.name, "Paris");
+        assert_eq!(reading.weather[0].description, "clear sky");
+        assert!((reading.main.temp_max - 15.0).abs() < f64::EPSILON);
+        assert!(reading.sys.sunrise < Some(reading.dt) && Some(reading.dt) < reading.sys.sunset);
+    }
+
+    #[test]
+    fn settings_override_the_defaults() {
+        let reading = CurrentWeather::builder()
+            .dt(DEFAULT_DT + 86_400)
+            .temp(25.0)
+            .temp_range(20.0, 28.0)
+            .wind(5.0, 180.0)
+            .clouds(None)
+            .rain_1h(0.5)
+            .extra("uvi", serde_json::json!(6.2))
+            .build();
+
+        assert_eq!(reading.sys.sunrise, Some(DEFAULT_DT + 86_400 + DEFAULT_SUNRISE));
+        assert!((reading.main.feels_like - 25.0).abs() < f64::EPSILON);
+        assert!((reading.main.temp_min - 20.0).abs() < f64::EPSILON);
+        assert_eq!(reading.wind.deg, Some(180.0));
+        assert!(reading.clouds.is_none());
+        assert_eq!(reading.rain.unwrap().one_hour, Some(0.5));
+        assert!(reading.snow.is_none());
+        assert_eq!(reading.extra["uvi"], 6.2);
+    }
+}
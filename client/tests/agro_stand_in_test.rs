@@ -0,0 +1,149 @@
+mod common;
+
+use common::StandIn;
+use hyper::{Method, StatusCode};
+use openweathermap_client::{
+    agro::{GeoJson, NewPolygon, SatelliteImageSearch},
+    error::ApiCallError,
+    models::Coord,
+    ApiHost, CachePolicy, Client,
+};
+
+const POLYGON: &str = r#"{"id":"5aaa8052cbbbb5000b73ff66","name":"North field","center":[-121.1867,37.6739],"area":190.9484,
+    "geo_json":{"type":"Feature","properties":{},"geometry":{"type":"Polygon",
+        "coordinates":[[[-121.1958,37.6683],[-121.1779,37.6683],[-121.1779,37.6792],[-121.1958,37.6683]]]}}}"#;
+
+#[tokio::test]
+async fn changes_evict_cached_polygons() {
+    let stand_in = StandIn::start(|request| match &request.method {
+        &Method::DELETE => (StatusCode::NO_CONTENT, String::new()),
+        _ => (StatusCode::OK, format!("[{POLYGON}]")),
+    })
+    .await;
+    let client = Client::builder()
+        .api_key("STAND_IN_KEY")
+        .base_url(ApiHost::Agro, stand_in.base_url())
+        .allow_insecure_http(true)
+        .cache(CachePolicy::default())
+        .build()
+        .unwrap();
+
+    client.list_polygons().await.unwrap();
+    client.list_polygons().await.unwrap();
+    assert_eq!(stand_in.received().len(), 1);
+
+    client.delete_polygon("5aaa8052cbbbb5000b73ff66").await.unwrap();
+    client.list_polygons().await.unwrap();
+
+    let methods: Vec<Method> = stand_in.received().into_iter().map(|r| r.method).collect();
+    assert_eq!(methods, [Method::GET, Method::DELETE, Method::GET]);
+}
+
+#[tokio::test]
+async fn polygon_crud() {
+    let stand_in = StandIn::start(|request| match (&request.method, request.path.as_str()) {
+        (&Method::POST, "/agro/1.0/polygons") => (StatusCode::CREATED, POLYGON.to_string()),
+        (&Method::GET, "/agro/1.0/polygons") => (StatusCode::OK, format!("[{POLYGON}]")),
+        (&Method::PUT, "/agro/1.0/polygons/5aaa8052cbbbb5000b73ff66") => {
+            (StatusCode::OK, POLYGON.replace("North field", "South field"))
+        }
+        (&Method::DELETE, "/agro/1.0/polygons/5aaa8052cbbbb5000b73ff66") => (StatusCode::NO_CONTENT, String::new()),
+        _ => (
+            StatusCode::NOT_FOUND,
+            r#"{"cod":404,"message":"not found"}"#.to_string(),
+        ),
+    })
+    .await;
+    let client = stand_in.client(ApiHost::Agro);
+
+    let created = client
+        .create_polygon(&NewPolygon {
+            name: "North field".to_string(),
+            geo_json: GeoJson::polygon(&[
+                Coord::new(37.6683, -121.1958),
+                Coord::new(37.6683, -121.1779),
+                Coord::new(37.6792, -121.1779),
+            ]),
+        })
+        .await
+        .unwrap();
+    assert_eq!(created.id, "5aaa8052cbbbb5000b73ff66");
+
+    assert_eq!(client.list_polygons().await.unwrap().len(), 1);
+    assert_eq!(
+        client.rename_polygon(&created.id, "South field").await.unwrap().name,
+        "South field"
+    );
+    client.delete_polygon(&created.id).await.unwrap();
+
+    let missing = client.fetch_polygon("unknown").await;
+    assert!(matches!(
+        missing,
+        Err(ApiCallError::InvalidResponsStatus {
+            status: StatusCode::NOT_FOUND,
+            ..
+        })
+    ));
+
+    let received = stand_in.received();
+    let create: serde_json::Value = serde_json::from_str(&received[0].body).unwrap();
+    assert_eq!(create["name"], "North field");
+    assert_eq!(create["geo_json"]["geometry"]["type"], "Polygon");
+    assert_eq!(received[0].query, "appid=STAND_IN_KEY");
+    assert_eq!(received[2].body, r#"{"name":"South field"}"#);
+    assert!(received[3].body.is_empty());
+}
+
+#[tokio::test]
+async fn soil_images_and_stats() {
+    let stand_in = StandIn::start(|request| match request.path.as_str() {
+        "/agro/1.0/soil" => (
+            StatusCode::OK,
+            r#"{"dt":1522108800,"t10":281.96,"moisture":0.175,"t0":279.02}"#.to_string(),
+        ),
+        "/agro/1.0/image/search" => (
+            StatusCode::OK,
+            r#"[{"dt":1500940800,"type":"Sentinel-2","dc":100,"cl":1.56,"image":{},
+                 "stats":{"ndvi":"http://api.agromonitoring.com/stats/1.0/02359768a00/5ac22f004b1ae4000b5b97cf?appid=OTHER"}}]"#
+                .to_string(),
+        ),
+        "/stats/1.0/02359768a00/5ac22f004b1ae4000b5b97cf" => (
+            StatusCode::OK,
+            r#"{"std":0.1,"p25":0.3,"num":57,"min":0.1,"max":0.8,"median":0.4,"p75":0.5,"mean":0.42}"#.to_string(),
+        ),
+        _ => (StatusCode::NOT_FOUND, String::new()),
+    })
+    .await;
+    let client = stand_in.client(ApiHost::Agro);
+
+    let soil = client.fetch_soil("5aaa8052cbbbb5000b73ff66").await.unwrap();
+    assert!((soil.moisture - 0.175).abs() < f64::EPSILON);
+
+    let images = client
+        .search_satellite_images(
+            "5aaa8052cbbbb5000b73ff66",
+            &SatelliteImageSearch::new(1_500_000_000, 1_510_000_000),
+        )
+        .await
+        .unwrap();
+    let stats = client
+        .fetch_index_stats(images[0].stats.ndvi.as_deref().unwrap())
+        .await
+        .unwrap();
+    assert!((stats.mean - 0.42).abs() < f64::EPSILON);
+
+    let received = stand_in.received();
+    assert_eq!(received[0].query, "polyid=5aaa8052cbbbb5000b73ff66&appid=STAND_IN_KEY");
+    assert_eq!(received[2].query, "appid=STAND_IN_KEY");
+}
+
+#[tokio::test]
+async fn polygon_ids_cant_change_the_request_target() {
+    let stand_in = StandIn::start(|_| (StatusCode::NO_CONTENT, String::new())).await;
+    let client = stand_in.client(ApiHost::Agro);
+
+    let result = client.delete_polygon("../polygons").await;
+
+    assert!(matches!(result, Err(ApiCallError::InvalidResourceId(_))));
+    assert!(stand_in.received().is_empty());
+}
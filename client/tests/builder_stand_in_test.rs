@@ -0,0 +1,202 @@
+mod common;
+
+use common::{StandIn, PARIS};
+use hyper::{header::USER_AGENT, StatusCode};
+use openweathermap_client::{
+    error::ApiCallError, models::City, stations::StationRegistration, ApiHost, CachePolicy, Client, ClientObserver,
+    RequestInfo, RetryPolicy,
+};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// Remembers the events it is notified of.
+#[derive(Default)]
+struct Events(Mutex<Vec<String>>);
+
+impl ClientObserver for Events {
+    fn on_retry(&self, request: &RequestInfo, delay: Duration) {
+        self.0.lock().unwrap().push(format!(
+            "retry {} after attempt {} in {delay:?}",
+            request.endpoint, request.attempt
+        ));
+    }
+
+    fn on_error(&self, request: &RequestInfo, _error: &ApiCallError, _latency: Duration) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("error {} on attempt {}", request.endpoint, request.attempt));
+    }
+
+    fn on_retry_after(&self, request: &RequestInfo, wait: Duration) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("wait {} {wait:?}", request.endpoint));
+    }
+
+    fn on_cache_hit(&self, request: &RequestInfo) {
+        self.0.lock().unwrap().push(format!("hit {}", request.endpoint));
+    }
+
+    fn on_cache_miss(&self, request: &RequestInfo) {
+        self.0.lock().unwrap().push(format!("miss {}", request.endpoint));
+    }
+}
+
+fn builder_for(stand_in: &StandIn) -> openweathermap_client::ClientBuilder {
+    Client::builder()
+        .api_key("STAND_IN_KEY")
+        .base_url(ApiHost::Api, stand_in.base_url())
+        .allow_insecure_http(true)
+}
+
+#[tokio::test]
+async fn user_agent_and_extra_headers_are_sent() {
+    let stand_in = StandIn::start(|_| (StatusCode::OK, PARIS.to_string())).await;
+    let client = builder_for(&stand_in)
+        .user_agent("weather-dashboard/1.2")
+        .header("X-Service-Name", "weather-dashboard")
+        .build()
+        .unwrap();
+
+    client.fetch_weather(&City::new("Paris", "FR")).await.unwrap();
+
+    let received = stand_in.received();
+    assert_eq!(received[0].headers[USER_AGENT], "weather-dashboard/1.2");
+    assert_eq!(received[0].headers["x-service-name"], "weather-dashboard");
+}
+
+#[tokio::test]
+async fn the_default_user_agent_names_the_client() {
+    let stand_in = StandIn::start(|_| (StatusCode::OK, PARIS.to_string())).await;
+    let client = builder_for(&stand_in).build().unwrap();
+
+    client.fetch_weather(&City::new("Paris", "FR")).await.unwrap();
+
+    let user_agent = stand_in.received()[0].headers[USER_AGENT].to_str().unwrap().to_string();
+    assert!(user_agent.starts_with("openweathermap_client/"));
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted = calls.clone();
+    let stand_in = StandIn::start(move |_| match counted.fetch_add(1, Ordering::SeqCst) {
+        0 => (StatusCode::SERVICE_UNAVAILABLE, "busy".to_string()),
+        _ => (StatusCode::OK, PARIS.to_string()),
+    })
+    .await;
+    let events = Arc::new(Events::default());
+    let mut client = builder_for(&stand_in)
+        .retry(RetryPolicy {
+            max_attempts: 2,
+            initial_backoff: Duration::from_millis(5),
+            max_backoff: Duration::from_millis(5),
+            ..RetryPolicy::default()
+        })
+        .build()
+        .unwrap();
+    client.add_observer(events.clone());
+
+    let reading = client.fetch_weather(&City::new("Paris", "FR")).await.unwrap();
+
+    assert_eq!(reading.name, "Paris");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(*events.0.lock().unwrap(), ["retry weather after attempt 1 in 5ms"]);
+}
+
+#[tokio::test]
+async fn retries_give_up_after_the_last_attempt() {
+    let stand_in = StandIn::start(|_| (StatusCode::BAD_GATEWAY, "down".to_string())).await;
+    let client = builder_for(&stand_in)
+        .retry(RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        })
+        .build()
+        .unwrap();
+
+    let error = client.fetch_weather(&City::new("Paris", "FR")).await.unwrap_err();
+
+    assert!(matches!(
+        error,
+        ApiCallError::InvalidResponsStatus {
+            status: StatusCode::BAD_GATEWAY,
+            ..
+        }
+    ));
+    assert_eq!(stand_in.received().len(), 3);
+}
+
+#[tokio::test]
+async fn failed_posts_are_not_retried() {
+    let stand_in = StandIn::start(|_| (StatusCode::BAD_GATEWAY, "down".to_string())).await;
+    let client = builder_for(&stand_in)
+        .retry(RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        })
+        .build()
+        .unwrap();
+
+    let result = client
+        .create_station(&StationRegistration {
+            external_id: "SF_TEST001".to_string(),
+            name: "San Francisco Test Station".to_string(),
+            latitude: 37.76,
+            longitude: -122.43,
+            altitude: None,
+        })
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(stand_in.received().len(), 1);
+}
+
+#[tokio::test]
+async fn cached_responses_are_not_fetched_again() {
+    let stand_in = StandIn::start(|_| (StatusCode::OK, PARIS.to_string())).await;
+    let events = Arc::new(Events::default());
+    let mut client = builder_for(&stand_in).cache(CachePolicy::default()).build().unwrap();
+    client.add_observer(events.clone());
+
+    for _ in 0..3 {
+        client.fetch_weather(&City::new("Paris", "FR")).await.unwrap();
+    }
+
+    assert_eq!(stand_in.received().len(), 1);
+    assert_eq!(
+        *events.0.lock().unwrap(),
+        ["miss weather", "hit weather", "hit weather"]
+    );
+}
+
+#[tokio::test]
+async fn slow_responses_time_out() {
+    let stand_in = StandIn::start(|_| {
+        std::thread::sleep(Duration::from_millis(200));
+        (StatusCode::OK, PARIS.to_string())
+    })
+    .await;
+    let events = Arc::new(Events::default());
+    let mut client = builder_for(&stand_in)
+        .request_timeout(Duration::from_millis(20))
+        .build()
+        .unwrap();
+    client.add_observer(events.clone());
+
+    let error = client.fetch_weather(&City::new("Paris", "FR")).await.unwrap_err();
+
+    assert!(matches!(error, ApiCallError::HttpError { .. }));
+    assert_eq!(*events.0.lock().unwrap(), ["error weather on attempt 1"]);
+}
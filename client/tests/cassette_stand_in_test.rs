@@ -0,0 +1,69 @@
+mod common;
+
+use common::StandIn;
+use hyper::StatusCode;
+use openweathermap_client::{cassette::Cassette, models::Coord, ApiHost, Client, ClientOptions};
+
+fn client(stand_in: &StandIn, api_key: &str, cassette: Cassette) -> Client {
+    Client::new(ClientOptions {
+        api_key: api_key.to_string(),
+        cassette: Some(cassette),
+        ..stand_in.client_options(ApiHost::Api)
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn record_then_replay_without_the_server() {
+    let path = std::env::temp_dir().join(format!("owm_cassette_stand_in_{}.yaml", std::process::id()));
+    let stand_in = StandIn::start(|_| {
+        (
+            StatusCode::OK,
+            r#"[{"lat":37.75,"lon":-122.37,"date_iso":"2017-06-23T12:00:00Z","date":1498219200,"value":10.16}]"#
+                .to_string(),
+        )
+    })
+    .await;
+
+    let recorder = client(&stand_in, "RECORDING_KEY", Cassette::record(&path));
+    let recorded = recorder
+        .fetch_uv_index_forecast(&Coord::new(37.75, -122.37), 1)
+        .await
+        .unwrap();
+    assert_eq!(stand_in.received().len(), 1);
+    assert!(!path.exists(), "written before the recorder is done");
+    drop(recorder);
+
+    let cassette = std::fs::read_to_string(&path).unwrap();
+    assert!(!cassette.contains("RECORDING_KEY"));
+    assert!(cassette.contains("appid=SCRUBBED"));
+
+    let player = client(&stand_in, "", Cassette::replay(&path));
+    let replayed = player
+        .fetch_uv_index_forecast(&Coord::new(37.75, -122.37), 1)
+        .await
+        .unwrap();
+    assert_eq!(stand_in.received().len(), 1);
+    assert_eq!(replayed[0].date, recorded[0].date);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn flushing_writes_the_calls_recorded_so_far() {
+    let path = std::env::temp_dir().join(format!("owm_cassette_flush_{}.yaml", std::process::id()));
+    let stand_in = StandIn::start(|_| (StatusCode::OK, "[]".to_string())).await;
+    let recorder = client(&stand_in, "RECORDING_KEY", Cassette::record(&path));
+
+    recorder.flush_cassette().unwrap();
+    assert!(!path.exists(), "nothing recorded yet");
+
+    recorder
+        .fetch_uv_index_forecast(&Coord::new(37.75, -122.37), 1)
+        .await
+        .unwrap();
+    recorder.flush_cassette().unwrap();
+    assert!(std::fs::read_to_string(&path).unwrap().contains("appid=SCRUBBED"));
+
+    std::fs::remove_file(path).unwrap();
+}
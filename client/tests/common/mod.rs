@@ -0,0 +1,107 @@
+//! A local stand-in for the API, answering each request with a canned response.
+
+// Each test file uses a different part of the stand-in.
+#![allow(dead_code)]
+
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Bytes, server::conn::http1, service::service_fn, HeaderMap, Method, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use openweathermap_client::{ApiHost, Client, ClientOptions};
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use tokio::net::TcpListener;
+
+/// A `/weather` response of light rain in Paris, in imperial units.
+pub const PARIS: &str = include_str!("../fixtures/current_weather/paris.json");
+
+/// A request received by the [`StandIn`].
+#[derive(Debug, Clone)]
+pub struct Received {
+    pub method: Method,
+    pub path: String,
+    pub query: String,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+pub type Route = dyn Fn(&Received) -> (StatusCode, String) + Send + Sync;
+
+pub struct StandIn {
+    pub address: SocketAddr,
+    received: Arc<Mutex<Vec<Received>>>,
+}
+
+impl StandIn {
+    /// Starts serving on a free local port, answering every request with `route`.
+    pub async fn start(route: impl Fn(&Received) -> (StatusCode, String) + Send + Sync + 'static) -> StandIn {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let route: Arc<Route> = Arc::new(route);
+
+        let log = received.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let log = log.clone();
+                let route = route.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |request: hyper::Request<hyper::body::Incoming>| {
+                        let log = log.clone();
+                        let route = route.clone();
+                        async move {
+                            let (parts, body) = request.into_parts();
+                            let body = body.collect().await.map(|b| b.to_bytes()).unwrap_or_default();
+                            let received = Received {
+                                method: parts.method,
+                                path: parts.uri.path().to_string(),
+                                query: parts.uri.query().unwrap_or_default().to_string(),
+                                headers: parts.headers,
+                                body: String::from_utf8_lossy(&body).to_string(),
+                            };
+                            let (status, content) = route(&received);
+                            log.lock().unwrap().push(received);
+                            let mut response = Response::new(Full::new(Bytes::from(content)));
+                            *response.status_mut() = status;
+                            Ok::<_, Infallible>(response)
+                        }
+                    });
+                    let _ = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+
+        StandIn { address, received }
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    pub fn received(&self) -> Vec<Received> {
+        self.received.lock().unwrap().clone()
+    }
+
+    /// Options for a client calling the stand-in in place of `host`, with the api key `STAND_IN_KEY`.
+    pub fn client_options(&self, host: ApiHost) -> ClientOptions {
+        let mut options = ClientOptions {
+            api_key: "STAND_IN_KEY".to_string(),
+            allow_insecure_http: true,
+            ..ClientOptions::default()
+        };
+        options.hosts.insert(host, self.base_url());
+        options
+    }
+
+    /// A client calling the stand-in in place of `host`, see [`StandIn::client_options`].
+    pub fn client(&self, host: ApiHost) -> Client {
+        Client::new(self.client_options(host)).unwrap()
+    }
+}
@@ -0,0 +1,92 @@
+//! Edge-case `/weather` responses seen in the wild, each in `tests/fixtures/current_weather`.
+
+use openweathermap_client::models::CurrentWeather;
+use std::path::Path;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/current_weather");
+
+/// Parses the fixture `name` the way [`Client`](openweathermap_client::Client) parses responses.
+fn parse(name: &str) -> CurrentWeather {
+    let fixture = std::fs::read_to_string(Path::new(FIXTURES).join(name)).unwrap();
+    serde_yaml::from_str(&fixture).unwrap_or_else(|e| panic!("{name}: {e}"))
+}
+
+#[test]
+fn every_fixture_parses() {
+    let mut names: Vec<String> = std::fs::read_dir(FIXTURES)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    assert!(names.len() >= 6);
+
+    for name in names {
+        parse(&name);
+    }
+}
+
+#[test]
+fn polar_night_has_no_sunrise_or_sunset() {
+    let reading = parse("polar_night.json");
+
+    assert_eq!(reading.name, "Longyearbyen");
+    assert_eq!(reading.sys.sunrise, None);
+    assert_eq!(reading.sys.sunset, None);
+    assert_eq!(reading.sys.type_, Some(1));
+}
+
+#[test]
+fn the_open_ocean_has_no_city_or_country() {
+    let reading = parse("open_ocean.json");
+
+    assert_eq!(reading.id, 0);
+    assert!(reading.name.is_empty());
+    assert_eq!(reading.sys.country, None);
+    assert_eq!(reading.sys.sunrise, Some(1_697_722_862));
+}
+
+#[test]
+fn calm_wind_has_no_direction() {
+    let reading = parse("calm_wind.json");
+
+    assert!(reading.wind.speed.abs() < f64::EPSILON);
+    assert_eq!(reading.wind.deg, None);
+    assert!(reading.clouds.is_none());
+    assert_eq!(reading.base, None);
+}
+
+#[test]
+fn unknown_fields_are_kept() {
+    let reading = parse("unknown_fields.json");
+
+    assert_eq!(reading.extra["uvi"], 2.1);
+    assert_eq!(reading.extra["air_quality"]["aqi"], 2);
+    assert_eq!(reading.weather[0].extra["severity"], "minor");
+    assert_eq!(reading.main.extra["temp_kf"], -0.42);
+    assert_eq!(reading.wind.extra["direction"], "WSW");
+    assert_eq!(reading.rain.as_ref().unwrap().extra["24h"], 4.1);
+    assert_eq!(reading.sys.extra["dst"], true);
+    assert!(reading.clouds.unwrap().extra.is_empty());
+}
+
+#[test]
+fn snow_may_only_be_given_for_three_hours() {
+    let reading = parse("snow_three_hours.json");
+    let snow = reading.snow.unwrap();
+
+    assert_eq!(snow.one_hour, None);
+    assert_eq!(snow.three_hour, Some(2.5));
+    assert_eq!(reading.visibility, None);
+    assert_eq!(reading.weather.len(), 2);
+}
+
+#[test]
+fn only_the_essential_fields_are_required() {
+    let reading = parse("minimal.json");
+
+    assert!(reading.weather.is_empty());
+    assert_eq!(reading.sys.country, None);
+    assert_eq!(reading.timezone, 0);
+    assert_eq!(reading.cod, None);
+    assert!(reading.extra.is_empty());
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::StandIn;
+use hyper::StatusCode;
+use openweathermap_client::{error::ApiCallError, history::HistoryRange, models::CityId, ApiHost, Client};
+
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+
+fn param(query: &str, name: &str) -> i64 {
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Every hourly reading from `start` up to and including `end`, like the History API answers.
+fn hourly_readings(query: &str) -> (StatusCode, String) {
+    let (start, end) = (param(query, "start"), param(query, "end"));
+    let list: Vec<String> = (start..=end)
+        .filter(|dt| dt % HOUR == 0)
+        .map(|dt| {
+            format!(
+                r#"{{"dt":{dt},"main":{{"temp":275.45,"feels_like":271.7,"pressure":1014,"humidity":74,"temp_min":274.26,"temp_max":276.48}},
+                "wind":{{"speed":2.16,"deg":87}},"clouds":{{"all":90}},"weather":[{{"id":804,"main":"Clouds","description":"overcast clouds","icon":"04n"}}]}}"#
+            )
+        })
+        .collect();
+    let body = format!(
+        r#"{{"message":"Count: {count}","cod":"200","city_id":2643743,"calctime":0.01,"cnt":{count},"list":[{}]}}"#,
+        list.join(","),
+        count = list.len()
+    );
+    (StatusCode::OK, body)
+}
+
+fn client(stand_in: &StandIn) -> Client {
+    Client::builder()
+        .api_key("STAND_IN_KEY")
+        .base_url(ApiHost::History, stand_in.base_url())
+        .allow_insecure_http(true)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn pages_dont_read_the_same_hour_twice() {
+    let stand_in = StandIn::start(|received| hourly_readings(&received.query)).await;
+    let start = 1_578_384_000;
+
+    let history = client(&stand_in)
+        .fetch_history(
+            &CityId::new(2_643_743),
+            &HistoryRange::Between {
+                start,
+                end: start + 16 * DAY,
+            },
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(stand_in.received().len(), 3);
+    let hours: HashSet<i64> = history.list.iter().map(|entry| entry.dt).collect();
+    assert_eq!(hours.len(), history.list.len(), "duplicate readings");
+    assert_eq!(history.list.len(), 16 * 24 + 1);
+    assert_eq!(history.cnt, 16 * 24 + 1);
+    assert!(history.list.windows(2).all(|pair| pair[1].dt - pair[0].dt == HOUR));
+}
+
+#[tokio::test]
+async fn empty_ranges_make_no_calls() {
+    let stand_in = StandIn::start(|received| hourly_readings(&received.query)).await;
+    let client = client(&stand_in);
+
+    for range in [
+        HistoryRange::Between { start: 20, end: 10 },
+        HistoryRange::Count { start: 10, count: 0 },
+    ] {
+        let result = client.fetch_history(&CityId::new(2_643_743), &range).await;
+        assert!(matches!(result, Err(ApiCallError::EmptyHistoryRange(r)) if r == range));
+    }
+    assert!(stand_in.received().is_empty());
+}
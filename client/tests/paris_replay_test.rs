@@ -0,0 +1,68 @@
+use hyper::{header::CONTENT_TYPE, StatusCode};
+use openweathermap_client::models::{City, CurrentWeather, UnitSystem};
+use openweathermap_client::{cassette::Cassette, error::ApiCallError, Client, ClientOptions};
+
+fn replaying_client() -> Client {
+    let options = ClientOptions {
+        api_key: "ANY_KEY_WILL_DO".to_string(),
+        units: UnitSystem::Metric,
+        language: "fr".to_string(),
+        cassette: Some(Cassette::replay(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/cassettes/paris.yaml"
+        ))),
+        ..ClientOptions::default()
+    };
+    Client::new(options).unwrap()
+}
+
+#[tokio::test]
+async fn paris_replay_test() {
+    let client = replaying_client();
+
+    let reading = client.fetch_weather(&City::new("Paris", "FR")).await.unwrap();
+
+    assert_eq!(reading.name, "Paris");
+    assert_eq!(reading.sys.country.as_deref(), Some("FR"));
+    assert!((reading.main.temp - 14.62).abs() < f64::EPSILON);
+    assert_eq!(reading.weather[0].description, "nuageux");
+}
+
+#[tokio::test]
+async fn raw_responses_keep_the_payload() {
+    let client = replaying_client();
+
+    let raw = client.fetch_weather_raw(&City::new("Paris", "FR")).await.unwrap();
+
+    assert_eq!(raw.status(), StatusCode::OK);
+    assert_eq!(raw.headers()[CONTENT_TYPE], "application/json; charset=utf-8");
+    assert!(raw.body().starts_with(br#"{"coord":{"lon":2.3488"#));
+    assert_eq!(
+        raw.url(),
+        "https://api.openweathermap.org/data/2.5/weather?units=metric&lang=fr&q=Paris%2CFR&appid=ANY****"
+    );
+    let reading: CurrentWeather = raw.parse().unwrap();
+    assert_eq!(reading.name, "Paris");
+}
+
+#[tokio::test]
+async fn recorded_errors_are_replayed() {
+    let client = replaying_client();
+
+    let error = client.fetch_weather(&City::new("Atlantis", "FR")).await.unwrap_err();
+    assert!(matches!(
+        error,
+        ApiCallError::InvalidResponsStatus {
+            status: StatusCode::NOT_FOUND,
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn unrecorded_requests_fail() {
+    let client = replaying_client();
+
+    let error = client.fetch_weather(&City::new("Lyon", "FR")).await.unwrap_err();
+    assert!(matches!(error, ApiCallError::CassetteMiss { .. }));
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::StandIn;
+use hyper::StatusCode;
+use openweathermap_client::{
+    models::{BoundingBox, Coord},
+    region::Region,
+    ApiHost, Client,
+};
+
+/// The reading of the station nearest to the queried point: one west of the 5th meridian, another east of it.
+fn station_reading(query: &str) -> (StatusCode, String) {
+    let lon: f64 = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("lon="))
+        .and_then(|lon| lon.parse().ok())
+        .unwrap_or_default();
+    let (id, name, temp, wind) = if lon < 5.0 {
+        (3_014_728, "Grenoble", 9.5, 2.1)
+    } else {
+        (3_031_137, "Bourg-Saint-Maurice", 3.25, 6.7)
+    };
+    let body = format!(
+        r#"{{"coord":{{"lon":{lon},"lat":45.2}},"weather":[{{"id":800,"main":"Clear","description":"clear sky","icon":"01d"}}],
+        "base":"stations","main":{{"temp":{temp},"feels_like":{temp},"temp_min":{temp},"temp_max":{temp},"pressure":1019,"humidity":72}},
+        "wind":{{"speed":{wind},"deg":240}},"clouds":{{"all":0}},"dt":1697713200,
+        "sys":{{"country":"FR","sunrise":1697695763,"sunset":1697734275}},"timezone":7200,"id":{id},"name":"{name}","cod":200}}"#
+    );
+    (StatusCode::OK, body)
+}
+
+#[tokio::test]
+async fn every_grid_point_is_fetched_and_snapped_points_are_flagged() {
+    let stand_in = StandIn::start(|received| station_reading(&received.query)).await;
+    let client = Client::builder()
+        .api_key("STAND_IN_KEY")
+        .base_url(ApiHost::Api, stand_in.base_url())
+        .allow_insecure_http(true)
+        .build()
+        .unwrap();
+    let region = Region::from(BoundingBox::new(45.0, 4.5, 45.4, 6.1));
+
+    let summary = client.fetch_region(&region, 25.0).await.unwrap();
+
+    let grid = region.grid(25.0).unwrap();
+    assert_eq!(stand_in.received().len(), grid.len());
+    assert_eq!(summary.grid_points, grid.len());
+    assert!(summary.failures.is_empty());
+    assert_eq!(summary.stations.len(), 2);
+    assert_eq!(summary.snapped().count(), 2);
+    assert_eq!(summary.min_temp, Some(3.25));
+    assert_eq!(summary.max_temp, Some(9.5));
+    assert_eq!(summary.mean_temp, Some(6.375));
+    assert_eq!(summary.max_wind_speed, Some(6.7));
+}
+
+#[tokio::test]
+async fn invalid_regions_make_no_calls() {
+    let stand_in = StandIn::start(|received| station_reading(&received.query)).await;
+    let client = Client::builder()
+        .api_key("STAND_IN_KEY")
+        .base_url(ApiHost::Api, stand_in.base_url())
+        .allow_insecure_http(true)
+        .build()
+        .unwrap();
+    let region = Region::Polygon(vec![Coord::new(45.0, 5.0), Coord::new(45.5, 5.5)]);
+
+    assert!(client.fetch_region(&region, 10.0).await.is_err());
+    assert!(stand_in.received().is_empty());
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::StandIn;
+use hyper::{Method, StatusCode};
+use openweathermap_client::{
+    error::ApiCallError,
+    stations::{Measurement, StationRegistration},
+    ApiHost,
+};
+
+#[tokio::test]
+async fn register_a_station_and_upload_measurements() {
+    let stand_in = StandIn::start(|request| match (&request.method, request.path.as_str()) {
+        (&Method::POST, "/data/3.0/stations") => (
+            StatusCode::CREATED,
+            r#"{"ID":"583436dd9643a9000196b8d6","external_id":"SF_TEST001","name":"San Francisco Test Station",
+                "latitude":37.76,"longitude":-122.43,"altitude":150}"#
+                .to_string(),
+        ),
+        (&Method::POST, "/data/3.0/measurements") => (StatusCode::NO_CONTENT, String::new()),
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            r#"{"cod":401,"message":"Invalid API key"}"#.to_string(),
+        ),
+    })
+    .await;
+    let client = stand_in.client(ApiHost::Api);
+
+    let station = client
+        .create_station(&StationRegistration {
+            external_id: "SF_TEST001".to_string(),
+            name: "San Francisco Test Station".to_string(),
+            latitude: 37.76,
+            longitude: -122.43,
+            altitude: Some(150.0),
+        })
+        .await
+        .unwrap();
+    client
+        .post_measurements(&[Measurement {
+            station_id: station.id.clone(),
+            dt: 1_479_817_340,
+            temperature: Some(18.7),
+            humidity: Some(81.0),
+            ..Measurement::default()
+        }])
+        .await
+        .unwrap();
+
+    let error = client.delete_station("../stations?x=").await.unwrap_err();
+    assert!(matches!(error, ApiCallError::InvalidResourceId(_)));
+
+    let error = client.fetch_station(&station.id).await.unwrap_err();
+    assert!(matches!(
+        error,
+        ApiCallError::InvalidResponsStatus {
+            status: StatusCode::UNAUTHORIZED,
+            ..
+        }
+    ));
+
+    let received = stand_in.received();
+    assert_eq!(received.len(), 3);
+    assert_eq!(received[0].query, "appid=STAND_IN_KEY");
+    assert_eq!(
+        received[1].body,
+        r#"[{"station_id":"583436dd9643a9000196b8d6","dt":1479817340,"temperature":18.7,"humidity":81.0}]"#
+    );
+}
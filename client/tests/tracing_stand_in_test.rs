@@ -0,0 +1,88 @@
+#![cfg(feature = "tracing")]
+
+mod common;
+
+use common::{StandIn, PARIS};
+use hyper::StatusCode;
+use openweathermap_client::{models::City, ApiHost, Client};
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Subscriber,
+};
+use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+/// Every field recorded on a span, as `(span, field, value)`.
+type Recorded = Arc<Mutex<Vec<(String, String, String)>>>;
+
+/// Remembers the fields of every span.
+struct CaptureFields(Recorded);
+
+struct FieldVisitor<'a> {
+    span: &'static str,
+    recorded: &'a Recorded,
+}
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.recorded
+            .lock()
+            .unwrap()
+            .push((self.span.to_string(), field.name().to_string(), format!("{value:?}")));
+    }
+}
+
+impl<S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>> Layer<S> for CaptureFields {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        attrs.record(&mut FieldVisitor {
+            span: attrs.metadata().name(),
+            recorded: &self.0,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let span = ctx.span(id).map_or("", |span| span.name());
+        values.record(&mut FieldVisitor {
+            span,
+            recorded: &self.0,
+        });
+    }
+}
+
+#[tokio::test]
+async fn call_spans_carry_the_masked_url_and_never_the_api_key() {
+    let recorded = Recorded::default();
+    let _subscriber =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(CaptureFields(recorded.clone())));
+    let stand_in = StandIn::start(|_| (StatusCode::OK, PARIS.to_string())).await;
+    let client = Client::builder()
+        .api_key("STAND_IN_KEY")
+        .base_url(ApiHost::Api, stand_in.base_url())
+        .allow_insecure_http(true)
+        .build()
+        .unwrap();
+
+    client.fetch_weather(&City::new("Paris", "FR")).await.unwrap();
+
+    assert!(stand_in.received()[0].query.contains("appid=STAND_IN_KEY"));
+    let recorded = recorded.lock().unwrap();
+    let field = |name: &str| {
+        recorded
+            .iter()
+            .find(|(span, field, _)| span == "owm_api_call" && field == name)
+            .map(|(_, _, value)| value.clone())
+            .unwrap_or_else(|| panic!("no {name} field in {recorded:?}"))
+    };
+    assert!(field("url").contains("appid=STA****"), "{}", field("url"));
+    assert_eq!(field("query"), "\"Paris, FR\"");
+    assert_eq!(field("endpoint"), "\"weather\"");
+    assert_eq!(field("status"), "200");
+    assert!(
+        recorded.iter().all(|(_, _, value)| !value.contains("STAND_IN_KEY")),
+        "{recorded:?}"
+    );
+}
@@ -0,0 +1,135 @@
+use hyper::{body::Bytes, Request, Response, StatusCode};
+use openweathermap_client::{
+    error::ApiCallError,
+    models::City,
+    transport::{TransportError, TransportFuture},
+    ApiKeySource, Client, ClientOptions, Transport,
+};
+use std::sync::{Arc, Mutex};
+
+/// Answers every request with the same canned response, remembering the URIs it was sent.
+struct MockTransport {
+    status: StatusCode,
+    body: &'static [u8],
+    sent: Mutex<Vec<String>>,
+}
+
+impl MockTransport {
+    fn answering(status: StatusCode, body: &'static [u8]) -> Arc<MockTransport> {
+        Arc::new(MockTransport {
+            status,
+            body,
+            sent: Mutex::new(Vec::new()),
+        })
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: Request<Bytes>) -> TransportFuture<'_> {
+        self.sent.lock().unwrap().push(request.uri().to_string());
+        let response = Response::builder()
+            .status(self.status)
+            .body(Bytes::from_static(self.body))
+            .map_err(TransportError::from);
+        Box::pin(async move { response })
+    }
+}
+
+/// Fails every request without a response.
+struct UnreachableTransport;
+
+impl Transport for UnreachableTransport {
+    fn send(&self, _: Request<Bytes>) -> TransportFuture<'_> {
+        Box::pin(async { Err(TransportError::from("connection refused")) })
+    }
+}
+
+fn options() -> ClientOptions {
+    ClientOptions {
+        api_key: "MOCK_API_KEY".to_string(),
+        ..ClientOptions::default()
+    }
+}
+
+#[tokio::test]
+async fn non_success_responses_carry_status_and_body() {
+    let transport = MockTransport::answering(StatusCode::NOT_FOUND, br#"{"cod":"404","message":"city not found"}"#);
+    let client = Client::with_transport(options(), transport.clone()).unwrap();
+
+    let error = client.fetch_weather(&City::new("Atlantis", "FR")).await.unwrap_err();
+
+    match error {
+        ApiCallError::InvalidResponsStatus { status, body } => {
+            assert_eq!(status, StatusCode::NOT_FOUND);
+            assert_eq!(body, r#"{"cod":"404","message":"city not found"}"#);
+        }
+        other => panic!("unexpected error {other:?}"),
+    }
+    assert_eq!(
+        transport.sent.lock().unwrap().as_slice(),
+        ["https://api.openweathermap.org/data/2.5/weather?units=metric&lang=en&q=Atlantis%2CFR&appid=MOCK_API_KEY"]
+    );
+}
+
+#[tokio::test]
+async fn non_utf8_error_bodies_are_described() {
+    let transport = MockTransport::answering(StatusCode::BAD_GATEWAY, &[0xff, 0xfe]);
+    let client = Client::with_transport(options(), transport).unwrap();
+
+    let error = client.fetch_weather(&City::new("Paris", "FR")).await.unwrap_err();
+
+    assert!(matches!(
+        error,
+        ApiCallError::InvalidResponsStatus { status: StatusCode::BAD_GATEWAY, ref body } if body.starts_with("Error obtaining response body")
+    ));
+}
+
+#[tokio::test]
+async fn unparseable_success_bodies_are_parse_errors() {
+    let transport = MockTransport::answering(StatusCode::OK, b"{\"name\": ");
+    let client = Client::with_transport(options(), transport).unwrap();
+
+    let error = client.fetch_weather(&City::new("Paris", "FR")).await.unwrap_err();
+
+    assert!(matches!(error, ApiCallError::ResponseParseError { .. }));
+}
+
+#[tokio::test]
+async fn transport_failures_mask_the_api_key() {
+    let client = Client::with_transport(options(), Arc::new(UnreachableTransport)).unwrap();
+
+    let error = client.fetch_weather(&City::new("Paris", "FR")).await.unwrap_err();
+
+    let ApiCallError::HttpError { url, .. } = &error else {
+        panic!("unexpected error {error:?}");
+    };
+    assert!(url.ends_with("appid=MOC****"));
+    assert!(!error.to_string().contains("MOCK_API_KEY"));
+}
+
+#[tokio::test]
+async fn unauthorized_pool_keys_are_taken_out_of_rotation() {
+    let transport = MockTransport::answering(StatusCode::UNAUTHORIZED, br#"{"cod":401}"#);
+    let client = Client::with_transport(
+        ClientOptions {
+            api_key_source: Some(ApiKeySource::Pool(vec![
+                "FIRST_KEY".to_string(),
+                "SECOND_KEY".to_string(),
+            ])),
+            ..options()
+        },
+        transport,
+    )
+    .unwrap();
+
+    for _ in 0..2 {
+        assert!(matches!(
+            client.fetch_weather(&City::new("Paris", "FR")).await,
+            Err(ApiCallError::InvalidResponsStatus { .. })
+        ));
+    }
+    assert!(matches!(
+        client.fetch_weather(&City::new("Paris", "FR")).await,
+        Err(ApiCallError::NoApiKeyAvailable)
+    ));
+}
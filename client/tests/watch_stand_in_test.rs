@@ -0,0 +1,47 @@
+mod common;
+
+use common::{StandIn, PARIS};
+use futures_util::StreamExt;
+use hyper::StatusCode;
+use openweathermap_client::{error::ApiCallError, models::City, ApiHost};
+use std::{
+    pin::pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// The Paris reading, made at `dt`.
+fn reading(dt: i64) -> String {
+    let mut reading: serde_json::Value = serde_json::from_str(PARIS).unwrap();
+    reading["dt"] = dt.into();
+    reading.to_string()
+}
+
+#[tokio::test]
+async fn only_new_readings_and_errors_are_yielded() {
+    let polls = Arc::new(AtomicUsize::new(0));
+    let counted = polls.clone();
+    let stand_in = StandIn::start(move |_| match counted.fetch_add(1, Ordering::SeqCst) {
+        0 | 1 => (StatusCode::OK, reading(1_697_713_200)),
+        2 => (StatusCode::SERVICE_UNAVAILABLE, "busy".to_string()),
+        _ => (StatusCode::OK, reading(1_697_713_800)),
+    })
+    .await;
+    let client = stand_in.client(ApiHost::Api);
+
+    let mut readings = pin!(client.watch(City::new("Paris", "FR"), Duration::from_millis(5)));
+
+    assert_eq!(readings.next().await.unwrap().unwrap().dt, 1_697_713_200);
+    assert!(matches!(
+        readings.next().await.unwrap(),
+        Err(ApiCallError::InvalidResponsStatus {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            ..
+        })
+    ));
+    assert_eq!(readings.next().await.unwrap().unwrap().dt, 1_697_713_800);
+    assert_eq!(polls.load(Ordering::SeqCst), 4);
+}
@@ -10,7 +10,7 @@ use std::{
 
 use openweathermap_client::{
     models::{City, CityId, Coord},
-    ClientOptions, Query,
+    AnyQuery, ClientOptions, Query,
 };
 
 use crate::ExporterError;
@@ -110,6 +110,23 @@ pub struct ExporterConfig {
     /// The [`CityId`]s to query weather for.
     #[serde(default = "Vec::new")]
     pub locations: Vec<CityId>,
+
+    /// Queries of any kind, each tagged with its `type`.  See [`AnyQuery`].
+    #[serde(default = "Vec::new")]
+    pub queries: Vec<AnyQuery>,
+
+    /// Also publish the UV index for each coordinate queried, in `coordinates` or as a `coord` query.
+    #[serde(default)]
+    pub publish_uv_index: bool,
+
+    /// Also publish the global horizontal solar irradiance for each coordinate queried, in `coordinates` or as a
+    /// `coord` query.  It is read once per local day.  Requires a subscription to the Solar Irradiance API.
+    #[serde(default)]
+    pub publish_solar_irradiance: bool,
+
+    /// Warn when the station read for one of the coordinates is further than this from them, km.  Must be > 0.
+    #[serde(default = "ExporterConfig::default_reading_distance_warning_km")]
+    pub reading_distance_warning_km: f64,
 }
 
 impl ExporterConfig {
@@ -122,6 +139,11 @@ impl ExporterConfig {
         60
     }
 
+    /// Defaults to 25
+    pub fn default_reading_distance_warning_km() -> f64 {
+        25.0
+    }
+
     /// Loads a configuration from a yaml or json file.
     ///
     /// Searches for the first file from the following list.  Once found stops, and loads it.
@@ -189,9 +211,9 @@ impl ExporterConfig {
     }
 
     pub(crate) fn validate(&self) -> Result<(), ExporterError> {
-        if self.cities.len() + self.coordinates.len() + self.locations.len() == 0 {
+        if self.cities.len() + self.coordinates.len() + self.locations.len() + self.queries.len() == 0 {
             return Err(ExporterError::ConfigValidationError {
-                message: "No cities or coordinates or locations were specified in the config".to_string(),
+                message: "No cities or coordinates or locations or queries were specified in the config".to_string(),
                 error: None,
             });
         }
@@ -203,6 +225,22 @@ impl ExporterConfig {
             });
         }
 
+        if self.reading_distance_warning_km.is_nan() || self.reading_distance_warning_km <= 0.0 {
+            return Err(ExporterError::ConfigValidationError {
+                message: "reading_distance_warning_km must > 0".to_string(),
+                error: None,
+            });
+        }
+
+        for query in self.query_iterator() {
+            if let Err(e) = query.validate() {
+                return Err(ExporterError::ConfigValidationError {
+                    message: format!("Invalid location {query:?}: {e}"),
+                    error: None,
+                });
+            }
+        }
+
         match self.owm.validate() {
             Ok(()) => Ok(()),
             Err(e) => Err(ExporterError::ConfigValidationError {
@@ -221,7 +259,8 @@ impl ExporterConfig {
         let cities = self.cities.iter().map(|c| c as &dyn Query);
         let coordinates = self.coordinates.iter().map(|c| c as &dyn Query);
         let locations = self.locations.iter().map(|c| c as &dyn Query);
-        cities.chain(coordinates).chain(locations)
+        let queries = self.queries.iter().map(|c| c as &dyn Query);
+        cities.chain(coordinates).chain(locations).chain(queries)
     }
 }
 
@@ -241,3 +280,68 @@ fn join_paths(pbs: &[PathBuf], separator: &str) -> String {
         .collect::<Vec<String>>()
         .join(separator)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(yaml: &str) -> ExporterConfig {
+        ExporterConfig::parse(Path::new("owm_exporter.yaml"), yaml).unwrap()
+    }
+
+    #[test]
+    fn valid_locations_pass() {
+        let config = parse(
+            "
+owm: { api_key: TEST_KEY }
+cities:
+  - { name: Bangkok, country_code: TH }
+  - { name: \"New York,NY\", country_code: US }
+",
+        );
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn invalid_locations_are_rejected() {
+        let config = parse(
+            "
+owm: { api_key: TEST_KEY }
+cities:
+  - { name: London, country_code: UK }
+",
+        );
+        match config.validate() {
+            Err(ExporterError::ConfigValidationError { message, .. }) => {
+                assert!(
+                    message.contains("\"UK\" is not an ISO 3166-1 alpha-2 country code"),
+                    "{message}"
+                );
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn reading_distance_warning_km_must_be_positive() {
+        let with_km = |km: &str| {
+            parse(&format!(
+                "
+owm: {{ api_key: TEST_KEY }}
+coordinates: [ {{ lat: 45.1885, lon: 5.7245 }} ]
+reading_distance_warning_km: {km}
+"
+            ))
+        };
+        assert!(with_km("0.5").validate().is_ok());
+
+        for km in ["0", "-10", ".nan"] {
+            match with_km(km).validate() {
+                Err(ExporterError::ConfigValidationError { message, .. }) => {
+                    assert_eq!(message, "reading_distance_warning_km must > 0");
+                }
+                other => panic!("{km}: {other:?}"),
+            }
+        }
+    }
+}
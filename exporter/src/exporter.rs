@@ -1,15 +1,24 @@
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use metrics::{describe_gauge, describe_histogram, gauge, histogram};
 use metrics_exporter_prometheus::PrometheusBuilder;
 use metrics_util::MetricKindMask;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::future::{ready, Ready};
 use std::net::SocketAddr;
-use std::sync::Arc;
-use std::time::{SystemTime, SystemTimeError};
-use tokio::sync::Mutex;
-use tokio::time::{interval, Duration, Interval, MissedTickBehavior};
-
-use openweathermap_client::models::CurrentWeather;
-use openweathermap_client::{Client, Query};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::{interval, Duration, MissedTickBehavior};
+use tower::buffer::Buffer;
+use tower::{Service, ServiceBuilder, ServiceExt};
+
+use http::StatusCode;
+use openweathermap_client::error::ApiCallError;
+use openweathermap_client::geo;
+use openweathermap_client::models::{Coord, CurrentWeather};
+use openweathermap_client::solar::{self, SolarInterval, SolarIrradianceResponse};
+use openweathermap_client::{Client, ClientObserver, Query, RequestInfo};
 
 #[allow(clippy::wildcard_imports)]
 use crate::metric_metadata::*;
@@ -17,11 +26,25 @@ use crate::metric_metadata::*;
 use crate::ExporterConfig;
 use crate::ExporterError;
 
+/// Requests that may wait for the rate limit at once.
+const RATE_LIMIT_BUFFER: usize = 64;
+
+/// Hands out the permits to call the API with, see [`call_permits`].
+type CallPermits = Buffer<(), Ready<Result<(), Infallible>>>;
+
 /// An exporter will indefinitely query weather for a collection of cities, coordinates or city ids and publish the weather details in prometheus exposition format.
 pub struct Exporter {
     config: ExporterConfig,
     client: Client,
-    rate_limiter: Arc<Mutex<Interval>>,
+    permits: CallPermits,
+    /// The offset of local time from UTC at each coordinate, in seconds, from its latest reading.
+    utc_offsets: Mutex<HashMap<String, i64>>,
+    /// The queries whose latest reading is further than `reading_distance_warning_km`, so that it is only warned
+    /// about when that starts.
+    far_readings: Mutex<HashSet<String>>,
+    /// The solar irradiance read at each coordinate and the local day it is for.  It is forecast for the whole
+    /// day, so it is only read again once the day changes.
+    solar_irradiance: Mutex<HashMap<String, (String, SolarIrradianceResponse)>>,
 }
 
 impl Exporter {
@@ -34,14 +57,17 @@ impl Exporter {
     pub fn new(config: ExporterConfig) -> Result<Exporter, ExporterError> {
         config.validate()?;
 
-        let rate_limit_duration_millis = (60000_u64 * 1000_u64 / u64::from(config.max_calls_per_minute)) / 1000_u64;
-        let mut rate_limiter = interval(Duration::from_millis(rate_limit_duration_millis));
-        rate_limiter.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut client = Client::new(config.owm.clone())?;
+        client.add_observer(Arc::new(CallMetricsObserver));
+        let permits = call_permits(Duration::from_secs(60) / u32::from(config.max_calls_per_minute));
 
         Ok(Exporter {
-            client: Client::new(config.owm.clone())?,
             config,
-            rate_limiter: Arc::new(Mutex::new(rate_limiter)),
+            client,
+            permits,
+            utc_offsets: Mutex::new(HashMap::new()),
+            far_readings: Mutex::new(HashSet::new()),
+            solar_irradiance: Mutex::new(HashMap::new()),
         })
     }
 
@@ -66,33 +92,132 @@ impl Exporter {
         poll_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
         poll_interval.reset(); // we want to start the first query immediately
 
+        let mut cycle: u64 = 0;
         loop {
+            cycle += 1;
+            self.poll_locations(cycle).await;
+
+            poll_interval.tick().await;
+        }
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    async fn poll_locations(&self, cycle: u64) {
+        let poll = async {
             for query in self.config.query_iterator() {
-                self.slow_down().await;
+                self.poll_location(query).await;
+            }
+            if self.config.publish_uv_index || self.config.publish_solar_irradiance {
+                for coord in self.config.query_iterator().filter_map(|query| query.coord()) {
+                    self.poll_coordinate_extras(coord).await;
+                }
+            }
+        };
+        #[cfg(feature = "tracing")]
+        let poll = tracing::Instrument::instrument(poll, tracing::info_span!("owm_poll_cycle", cycle));
+        poll.await;
+
+        // The client is never dropped, a recording cassette is written after each cycle instead
+        if let Err(e) = self.client.flush_cassette() {
+            error!("Error writing the cassette. Error: {e}");
+        }
+    }
 
-                info!("Getting weather for {query:?}");
+    async fn poll_location(&self, query: &dyn Query) {
+        let poll = async {
+            info!("Getting weather for {query:?}");
+
+            self.permit().await;
+            match self.client.fetch_weather(query).await {
+                Ok(reading) => self.update_metrics_for_successful_query(query, &reading),
+                Err(e) => {
+                    self.update_metrics_for_failed_query(query);
+                    error!("Error reading weather for {query:?}. Error: {e:?}");
+                }
+            }
+        };
+        #[cfg(feature = "tracing")]
+        let poll = tracing::Instrument::instrument(poll, tracing::info_span!("owm_location", location = %query));
+        poll.await;
+    }
 
-                let start = SystemTime::now();
-                let reading = self.client.fetch_weather(query).await;
-                let call_duration = &SystemTime::now().duration_since(start);
+    /// Reads the optional UV index and solar irradiance for a coordinate.
+    async fn poll_coordinate_extras(&self, coord: &Coord) {
+        let poll = async {
+            let labels = labels_for_query(coord);
 
-                update_call_time_metrics(call_duration);
+            if self.config.publish_uv_index {
+                self.permit().await;
+                match self.client.fetch_uv_index(coord).await {
+                    Ok(uvi) => gauge!(OWM_UV_INDEX.name(), &labels).set(uvi.value),
+                    Err(e) => error!("Error reading UV index for {coord:?}. Error: {e:?}"),
+                }
+            }
 
-                match reading {
-                    Ok(reading) => self.update_metrics_for_successful_query(query, &reading),
-                    Err(e) => {
-                        self.update_metrics_for_failed_query(query);
-                        error!("Error reading weather for {query:?}. Error: {e:?}");
+            if self.config.publish_solar_irradiance {
+                let Some(utc_offset) = self.utc_offset(coord) else {
+                    error!("No reading yet giving the local time at {coord:?}, can't read its solar irradiance");
+                    return;
+                };
+                let now = unix_now();
+                let Some(irradiance) = self.solar_irradiance_today(coord, now, utc_offset).await else {
+                    return;
+                };
+                match irradiance.hourly_at(now) {
+                    Some(period) => {
+                        for (sky, values) in [("clear", period.clear_sky), ("cloudy", period.cloudy_sky)] {
+                            let mut labels = labels.clone();
+                            labels.push(("sky", sky.to_string()));
+                            gauge!(OWM_SOLAR_GHI.name(), &labels).set(values.ghi);
+                        }
                     }
+                    None => error!("No solar irradiance for the current hour at {coord:?}"),
+                }
+                if let Ok(mut cache) = self.solar_irradiance.lock() {
+                    cache.insert(coord.to_string(), (solar::local_date(now, utc_offset), irradiance));
                 }
             }
+        };
+        #[cfg(feature = "tracing")]
+        let poll = tracing::Instrument::instrument(poll, tracing::info_span!("owm_location", location = %coord));
+        poll.await;
+    }
 
-            poll_interval.tick().await;
+    /// The solar irradiance at the coordinate on the local day containing `now`, read once per day.
+    async fn solar_irradiance_today(
+        &self,
+        coord: &Coord,
+        now: i64,
+        utc_offset: i64,
+    ) -> Option<SolarIrradianceResponse> {
+        let today = solar::local_date(now, utc_offset);
+        let cached = self.solar_irradiance.lock().ok()?.remove(&coord.to_string());
+        if let Some((date, irradiance)) = cached {
+            if date == today {
+                return Some(irradiance);
+            }
+        }
+
+        self.permit().await;
+        match self
+            .client
+            .fetch_solar_irradiance(coord, now, utc_offset, SolarInterval::Hourly)
+            .await
+        {
+            Ok(irradiance) => Some(irradiance),
+            Err(e) => {
+                error!("Error reading solar irradiance for {coord:?}. Error: {e:?}");
+                None
+            }
         }
     }
 
-    async fn slow_down(&self) {
-        self.rate_limiter.lock().await.tick().await;
+    /// Waits until the rate limit allows another API call.  Waiting here, rather than in the client's transport,
+    /// keeps the wait out of the call times the client reports.
+    async fn permit(&self) {
+        if let Err(e) = self.permits.clone().oneshot(()).await {
+            error!("Rate limit unavailable, calling the API anyway. Error: {e}");
+        }
     }
 
     fn init_prometheus_exporter(&self) -> Result<(), ExporterError> {
@@ -120,6 +245,17 @@ impl Exporter {
 
         let labels = labels_for(query, reading);
         self.write_reading_values(reading, &labels);
+        if let Some(coord) = query.coord() {
+            if let Ok(mut utc_offsets) = self.utc_offsets.lock() {
+                utc_offsets.insert(coord.to_string(), reading.timezone);
+            }
+            self.write_reading_distance(query, coord, reading, &labels);
+        }
+    }
+
+    /// The offset of local time from UTC at the coordinate, as of its latest reading.
+    fn utc_offset(&self, coord: &Coord) -> Option<i64> {
+        self.utc_offsets.lock().ok()?.get(&coord.to_string()).copied()
     }
 
     fn update_metrics_for_failed_query(&self, query: &dyn Query) {
@@ -158,9 +294,54 @@ impl Exporter {
             OWM_RAIN_3H,
             OWM_SNOW_1H,
             OWM_SNOW_3H,
+            OWM_READING_DISTANCE,
         ] {
             describe_gauge!(m.name(), m.description());
         }
+
+        if self.config.publish_uv_index {
+            describe_gauge!(OWM_UV_INDEX.name(), OWM_UV_INDEX.description());
+        }
+        if self.config.publish_solar_irradiance {
+            describe_gauge!(OWM_SOLAR_GHI.name(), OWM_SOLAR_GHI.description());
+        }
+    }
+
+    /// Publishes how far the station read is from the coordinates queried, warning when it becomes too far to be
+    /// representative of them.
+    fn write_reading_distance(
+        &self,
+        query: &dyn Query,
+        coord: &Coord,
+        reading: &CurrentWeather,
+        labels: &Vec<(&'static str, String)>,
+    ) {
+        let distance_km = geo::haversine_km(coord, &reading.coord);
+        gauge!(OWM_READING_DISTANCE.name(), labels).set(distance_km);
+
+        let far = distance_km > self.config.reading_distance_warning_km;
+        let changed = match self.far_readings.lock() {
+            Ok(mut far_readings) if far => far_readings.insert(query.to_string()),
+            Ok(mut far_readings) => far_readings.remove(&query.to_string()),
+            Err(_) => true,
+        };
+        if !changed {
+            return;
+        }
+        if far {
+            warn!(
+                "The reading for {query} is of {:?} at {:.1} km, bearing {:.0}°, further than {} km",
+                reading.name,
+                distance_km,
+                geo::bearing(coord, &reading.coord),
+                self.config.reading_distance_warning_km
+            );
+        } else {
+            info!(
+                "The reading for {query} is of {:?} at {:.1} km, within {} km again",
+                reading.name, distance_km, self.config.reading_distance_warning_km
+            );
+        }
     }
 
     fn write_reading_values(&self, reading: &CurrentWeather, labels: &Vec<(&'static str, String)>) {
@@ -174,11 +355,12 @@ impl Exporter {
         gauge!(OWM_PRESSURE.name(), labels).set(reading.main.pressure);
         gauge!(OWM_HUMIDITY_PERCENT.name(), labels).set(reading.main.humidity);
         gauge!(owm_wind_speed(units).name(), labels).set(reading.wind.speed);
-        gauge!(OWM_WIND_DIRECTION.name(), labels).set(reading.wind.deg);
+        // NaN rather than leaving the previous reading's value up when this one has none
+        gauge!(OWM_WIND_DIRECTION.name(), labels).set(reading.wind.deg.unwrap_or(f64::NAN));
         if let Some(gust) = reading.wind.gust {
             gauge!(owm_wind_gust(units).name(), labels).set(gust);
         }
-        gauge!(OWM_CLOUDINESS_PERCENT.name(), labels).set(reading.clouds.cloudiness);
+        gauge!(OWM_CLOUDINESS_PERCENT.name(), labels).set(reading.clouds.as_ref().map_or(f64::NAN, |c| c.cloudiness));
 
         #[allow(clippy::cast_precision_loss)] // precision loss is not going to matter in anyone's lifetime
         if let Some(visibility) = reading.visibility {
@@ -205,14 +387,60 @@ impl Exporter {
     }
 }
 
-fn update_call_time_metrics(call_duration: &Result<Duration, SystemTimeError>) {
-    if let Ok(duration) = call_duration {
-        #[allow(clippy::cast_precision_loss)]
-        // precision loss unimportantwe onlu really coare for values in a range of about 10^8 microseconds
-        histogram!(OWM_API_CALL_TIME_HIST.name()).record(duration.as_micros() as f64 / 1000.);
+/// Grants every request straight away.  Behind a rate limit, it hands out the permits to call the API with.
+#[derive(Clone, Copy)]
+struct Permit;
+
+impl Service<()> for Permit {
+    type Response = ();
+    type Error = Infallible;
+    type Future = Ready<Result<(), Infallible>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, (): ()) -> Self::Future {
+        ready(Ok(()))
     }
 }
 
+/// One permit per `period`, shared by every API call the exporter makes, whatever the endpoint.  The buffer lets
+/// clones share the limit.
+fn call_permits(period: Duration) -> CallPermits {
+    ServiceBuilder::new()
+        .buffer(RATE_LIMIT_BUFFER)
+        .rate_limit(1, period)
+        .service(Permit)
+}
+
+/// Publishes metrics about the calls the [`Client`] makes.
+struct CallMetricsObserver;
+
+impl ClientObserver for CallMetricsObserver {
+    fn on_response(&self, _request: &RequestInfo, status: StatusCode, latency: Duration) {
+        record_call_time(status.as_str(), latency);
+    }
+
+    fn on_error(&self, _request: &RequestInfo, _error: &ApiCallError, latency: Duration) {
+        record_call_time("error", latency);
+    }
+}
+
+/// Records a call's latency, labelled with the response status or `"error"` when there was no response.
+fn record_call_time(status: &str, latency: Duration) {
+    #[allow(clippy::cast_precision_loss)]
+    // precision loss unimportantwe onlu really coare for values in a range of about 10^8 microseconds
+    histogram!(OWM_API_CALL_TIME_HIST.name(), "status" => status.to_string())
+        .record(latency.as_micros() as f64 / 1000.);
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX))
+}
+
 fn labels_for_query(query: &dyn Query) -> Vec<(&'static str, String)> {
     let mut labels = query.query_params();
     add_display_name(query, &mut labels);
@@ -243,3 +471,123 @@ fn labels_for(query: &dyn Query, reading: &CurrentWeather) -> Vec<(&'static str,
 
     labels
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::{Request, Response};
+    use hyper::body::Bytes;
+    use openweathermap_client::{
+        transport::{Transport, TransportFuture},
+        ClientOptions,
+    };
+    use std::time::Instant;
+
+    /// Answers every request at once.
+    struct Immediate;
+
+    impl Transport for Immediate {
+        fn send(&self, _: Request<Bytes>) -> TransportFuture<'_> {
+            Box::pin(async { Ok(Response::new(Bytes::from_static(b"{}"))) })
+        }
+    }
+
+    /// Remembers the latency of every response.
+    #[derive(Default)]
+    struct Latencies(Mutex<Vec<Duration>>);
+
+    impl ClientObserver for Latencies {
+        fn on_response(&self, _request: &RequestInfo, _status: StatusCode, latency: Duration) {
+            self.0.lock().unwrap().push(latency);
+        }
+    }
+
+    /// Answers like the weather, UV index and solar irradiance APIs, remembering the path of every request.
+    #[derive(Default)]
+    struct Paths(Mutex<Vec<String>>);
+
+    impl Transport for Paths {
+        fn send(&self, request: Request<Bytes>) -> TransportFuture<'_> {
+            let path = request.uri().path().to_string();
+            self.0.lock().unwrap().push(path.clone());
+            Box::pin(async move {
+                let body = match path.as_str() {
+                    "/data/2.5/weather" => {
+                        include_str!("../../client/tests/fixtures/current_weather/minimal.json").to_string()
+                    }
+                    "/data/2.5/uvi" => r#"{"lat":48.85,"lon":2.35,"date":1700049600,"value":1.5}"#.to_string(),
+                    _ => {
+                        let hourly: Vec<String> = (0..24)
+                            .map(|hour| format!(r#"{{"hour":{hour},"clear_sky":{{"ghi":{hour},"dni":0,"dhi":0}},"cloudy_sky":{{"ghi":{hour},"dni":0,"dhi":0}}}}"#))
+                            .collect();
+                        format!(
+                            r#"{{"lat":48.85,"lon":2.35,"date":"2023-11-15","tz":"+01:00","irradiance":{{"hourly":[{}]}}}}"#,
+                            hourly.join(",")
+                        )
+                    }
+                };
+                Ok(Response::new(Bytes::from(body)))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn coord_queries_get_the_extras_and_solar_irradiance_is_read_once_a_day() {
+        let config: ExporterConfig = serde_yaml::from_str(
+            "
+owm: { api_key: TEST_KEY }
+max_calls_per_minute: 60000
+queries:
+  - { type: coord, lat: 51.5085, lon: -0.1257 }
+publish_uv_index: true
+publish_solar_irradiance: true
+",
+        )
+        .unwrap();
+        let paths = Arc::new(Paths::default());
+        let exporter = Exporter {
+            client: Client::with_transport(config.owm.clone(), paths.clone()).unwrap(),
+            permits: call_permits(Duration::from_millis(1)),
+            config,
+            utc_offsets: Mutex::new(HashMap::new()),
+            far_readings: Mutex::new(HashSet::new()),
+            solar_irradiance: Mutex::new(HashMap::new()),
+        };
+
+        exporter.poll_locations(0).await;
+        exporter.poll_locations(1).await;
+
+        let paths = paths.0.lock().unwrap();
+        let count = |path: &str| paths.iter().filter(|p| *p == path).count();
+        assert_eq!(count("/data/2.5/weather"), 2, "{paths:?}");
+        assert_eq!(count("/data/2.5/uvi"), 2, "{paths:?}");
+        assert_eq!(count("/energy/1.0/solar/data"), 1, "{paths:?}");
+    }
+
+    #[tokio::test]
+    async fn rate_limited_calls_report_only_the_http_time() {
+        let permits = call_permits(Duration::from_millis(100));
+        let latencies = Arc::new(Latencies::default());
+        let options = ClientOptions {
+            api_key: "TEST_KEY".to_string(),
+            ..ClientOptions::default()
+        };
+        let mut client = Client::with_transport(options, Arc::new(Immediate)).unwrap();
+        client.add_observer(latencies.clone());
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            permits.clone().oneshot(()).await.unwrap();
+            let raw = client.fetch_weather_raw(&Coord::new(45.1885, 5.7245)).await.unwrap();
+            assert!(raw.elapsed() < Duration::from_millis(50), "{:?}", raw.elapsed());
+        }
+
+        assert!(start.elapsed() >= Duration::from_millis(200), "{:?}", start.elapsed());
+        let latencies = latencies.0.lock().unwrap();
+        assert_eq!(latencies.len(), 3);
+        assert!(
+            latencies.iter().all(|latency| *latency < Duration::from_millis(50)),
+            "{latencies:?}"
+        );
+    }
+}
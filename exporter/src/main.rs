@@ -72,7 +72,21 @@ pub use exporter::Exporter;
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<(), ExporterError> {
-    env_logger::init();
+    init_logging();
     let config = ExporterConfig::load()?;
     Exporter::new(config)?.run().await
 }
+
+#[cfg(not(feature = "tracing"))]
+fn init_logging() {
+    env_logger::init();
+}
+
+/// Logs through a `tracing` subscriber instead, which also picks up records from the `log` crate.  `RUST_LOG` still
+/// controls the level.
+#[cfg(feature = "tracing")]
+fn init_logging() {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+}
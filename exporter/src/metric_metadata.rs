@@ -48,7 +48,8 @@ const OWM_WIND_SPEED_METERS_PER_SECOND: &MetricMetadata =
     &new_metric("owm_wind_speed_meters_per_second", "Wind speed in meters/second");
 const OWM_WIND_SPEED_MILES_PER_HOUR: &MetricMetadata =
     &new_metric("owm_wind_speed_miles_per_hour", "Wind speed in miles per hour");
-pub const OWM_CLOUDINESS_PERCENT: &MetricMetadata = &new_metric("owm_cloudiness_percent", "% cloudiness");
+pub const OWM_CLOUDINESS_PERCENT: &MetricMetadata =
+    &new_metric("owm_cloudiness_percent", "% cloudiness, NaN when unknown");
 pub const OWM_HUMIDITY_PERCENT: &MetricMetadata = &new_metric("owm_humidity_percent", "% Humidity");
 pub const OWM_PRESSURE: &MetricMetadata = &new_metric("owm_pressure_hpa", "Atmospheric pressure in hPa");
 pub const OWM_RAIN_1H: &MetricMetadata = &new_metric("owm_rain_1h_mm", "1-hour rain accumulation in mm");
@@ -60,8 +61,19 @@ pub const OWM_TIMESTAMP_SECONDS: &MetricMetadata = &new_metric(
     "Timestamp of last reading in seconds since UNIX epoch",
 );
 pub const OWM_VISIBILITY: &MetricMetadata = &new_metric("owm_visibility_meters", "Visibility in meters, 10000 max");
-pub const OWM_WIND_DIRECTION: &MetricMetadata =
-    &new_metric("owm_wind_direction_degrees", "Wind direction in degrees (0-360)");
+pub const OWM_UV_INDEX: &MetricMetadata = &new_metric("owm_uv_index", "UV index");
+pub const OWM_SOLAR_GHI: &MetricMetadata = &new_metric(
+    "owm_solar_ghi_watts_per_m2",
+    "Global horizontal irradiance for the current hour in W/m², under a clear or cloudy sky",
+);
+pub const OWM_READING_DISTANCE: &MetricMetadata = &new_metric(
+    "owm_reading_distance_km",
+    "Distance in km from the queried coordinates to those of the station that was read",
+);
+pub const OWM_WIND_DIRECTION: &MetricMetadata = &new_metric(
+    "owm_wind_direction_degrees",
+    "Wind direction in degrees (0-360), NaN when unknown",
+);
 
 pub fn owm_temperature(units: UnitSystem) -> &'static MetricMetadata<'static> {
     match units {
@@ -95,5 +107,5 @@ pub fn owm_wind_speed(units: UnitSystem) -> &'static MetricMetadata<'static> {
 
 pub const OWM_API_CALL_TIME_HIST: &MetricMetadata = &new_metric(
     "owm_api_call_time_milliseconds",
-    "Histogram of successful call times per location in milliseconds",
+    "Histogram of call times in milliseconds, by response status or \"error\" when there was none",
 );